@@ -0,0 +1,969 @@
+/**
+ * Arbitrary precision unsigned integers.
+ *
+ * Numbers are stored as little endian limbs in base 2^32, so the product of two limbs always fits in a u64.
+ * Multiplication picks an algorithm by operand size: schoolbook for small numbers, then Karatsuba, then Toom-Cook
+ * 3-way, and a number theoretic transform (NTT) for numbers with hundreds of thousands of digits.
+ *
+ * Decimal conversion of large numbers splits them in two at a power 10^(9.2^i), and converts both halves the same
+ * way, so it runs at the speed of multiplication instead of in O(n^2). Parsing joins the halves as high.10^k + low.
+ * Printing divides by the power with Barrett reduction: with r close to B^2m / d precomputed by Newton's iteration,
+ * x / d is floor(x.r / B^2m) or only a few more.
+ */
+use std::cmp::{max, min, Ordering};
+use std::error::Error;
+use std::fmt;
+use std::ops::{Add, Mul, Sub};
+use std::str::FromStr;
+
+// Below this many limbs, schoolbook multiplication is faster than Karatsuba's extra additions.
+// Thresholds come from runs of big_uint_benchmark.rs over several seeds, run it to find them for a given machine.
+pub const KARATSUBA_THRESHOLD: usize = 48;
+
+// Below this many limbs, Karatsuba is faster than Toom-3's evaluation and interpolation. The benchmark has Toom-3
+// winning from somewhere between 384 and 768 limbs.
+pub const TOOM3_THRESHOLD: usize = 512;
+
+// From this many limbs onwards, the O(n log n) transform beats the Toom-3 recursion. Its padding to a power of two
+// makes it lose again just above, at 3072 limbs in some runs, but it wins by more at 2048.
+pub const NTT_THRESHOLD: usize = 2048;
+
+// Goldilocks prime 2^64 - 2^32 + 1. As p - 1 is divisible by 2^32, transforms can be up to 2^32 long.
+const NTT_PRIME: u64 = 0xffff_ffff_0000_0001;
+
+// 2^64 mod p, used to fold the high bits of products back into range.
+const NTT_EPSILON: u64 = 0xffff_ffff;
+
+// 7 generates the multiplicative group modulo the prime.
+const NTT_GENERATOR: u64 = 7;
+
+// Limbs are split into 16 bit digits for the transform, so convolution sums stay below the prime.
+const NTT_DIGIT_BITS: u32 = 16;
+
+// Largest power of 10 that fits in a limb, used to convert to and from decimal strings.
+const DECIMAL_BASE: u32 = 1_000_000_000;
+const DECIMAL_BASE_DIGITS: usize = 9;
+
+// Numbers of up to this many limbs are converted to and from decimal 9 digits at a time.
+const DECIMAL_SPLIT_THRESHOLD: usize = 64;
+
+// Number of hex digits in a limb.
+const HEX_LIMB_DIGITS: usize = 8;
+
+// Operand sizes (in limbs of the shorter operand) at which multiplication switches algorithm.
+#[derive(Debug, Clone, Copy)]
+pub struct MulThresholds {
+    pub karatsuba: usize,
+    pub toom3: usize,
+    pub ntt: usize,
+}
+
+impl Default for MulThresholds {
+    fn default() -> MulThresholds {
+        MulThresholds {
+            karatsuba: KARATSUBA_THRESHOLD,
+            toom3: TOOM3_THRESHOLD,
+            ntt: NTT_THRESHOLD,
+        }
+    }
+}
+
+// Unsigned integer of any size.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BigUint {
+    // Limbs from least to most significant, without any trailing zero limbs. Zero has no limbs.
+    limbs: Vec<u32>,
+}
+
+// Reasons a string can't be parsed as a number.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseBigUintError {
+    // String has no digits.
+    Empty,
+    // Character is not a digit of the given radix.
+    InvalidDigit(char),
+    // Only decimal and hex strings are supported.
+    UnsupportedRadix(u32),
+}
+
+impl fmt::Display for ParseBigUintError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseBigUintError::Empty => write!(f, "cannot parse number from empty string"),
+            ParseBigUintError::InvalidDigit(c) => write!(f, "invalid digit '{}' found in string", c),
+            ParseBigUintError::UnsupportedRadix(radix) => write!(f, "radix {} is not supported", radix),
+        }
+    }
+}
+
+impl Error for ParseBigUintError {}
+
+impl BigUint {
+    // Returns zero.
+    pub fn zero() -> BigUint {
+        BigUint { limbs: Vec::new() }
+    }
+
+    // Converts a primitive into a big number.
+    pub fn from_u64(value: u64) -> BigUint {
+        BigUint::from_limbs(vec![value as u32, (value >> 32) as u32])
+    }
+
+    // Builds a number from little endian limbs.
+    pub fn from_limbs(mut limbs: Vec<u32>) -> BigUint {
+        trim(&mut limbs);
+        BigUint { limbs }
+    }
+
+    // Little endian limbs of this number.
+    pub fn limbs(&self) -> &[u32] {
+        &self.limbs
+    }
+
+    // Returns true if the number is zero.
+    pub fn is_zero(&self) -> bool {
+        self.limbs.is_empty()
+    }
+
+    // Parses a decimal (radix 10) or hex (radix 16, optional "0x" prefix) string.
+    pub fn from_str_radix(string: &str, radix: u32) -> Result<BigUint, ParseBigUintError> {
+        let digits = match radix {
+            10 => string,
+            16 => string.strip_prefix("0x").unwrap_or(string),
+            _ => return Err(ParseBigUintError::UnsupportedRadix(radix)),
+        };
+
+        if digits.is_empty() {
+            return Err(ParseBigUintError::Empty);
+        }
+
+        // Validate once, so the conversion below can work on raw bytes.
+        if let Some(c) = digits.chars().find(|c| !c.is_digit(radix)) {
+            return Err(ParseBigUintError::InvalidDigit(c));
+        }
+        let bytes = digits.as_bytes();
+
+        let mut limbs = Vec::new();
+        if radix == 10 {
+            // Powers 10^(9.2^i) shorter than the string.
+            let mut powers = vec![BigUint::from_u64(DECIMAL_BASE as u64)];
+            while DECIMAL_BASE_DIGITS << powers.len() < bytes.len() {
+                let square = powers.last().unwrap() * powers.last().unwrap();
+                powers.push(square);
+            }
+            return Ok(parse_decimal(bytes, &powers));
+        } else {
+            // Every 8 hex digits from the right make up one limb.
+            let mut end = bytes.len();
+            while end > 0 {
+                let start = end.saturating_sub(HEX_LIMB_DIGITS);
+                limbs.push(parse_chunk(&bytes[start..end], 16));
+                end = start;
+            }
+        }
+
+        Ok(BigUint::from_limbs(limbs))
+    }
+
+    // Returns self - other, or None if other is bigger.
+    pub fn checked_sub(&self, other: &BigUint) -> Option<BigUint> {
+        if *self < *other {
+            return None;
+        }
+
+        let mut limbs = self.limbs.clone();
+        sub_in_place(&mut limbs, &other.limbs);
+        Some(BigUint::from_limbs(limbs))
+    }
+
+    // Multiplies using the grade school method in O(n^2) limb operations.
+    pub fn mul_schoolbook(&self, other: &BigUint) -> BigUint {
+        BigUint::from_limbs(schoolbook(&self.limbs, &other.limbs))
+    }
+
+    // Multiplies using Karatsuba's method, switching to schoolbook once an operand is shorter than [threshold] limbs.
+    pub fn mul_karatsuba(&self, other: &BigUint, threshold: usize) -> BigUint {
+        BigUint::from_limbs(karatsuba(&self.limbs, &other.limbs, threshold))
+    }
+
+    // Multiplies using Toom-Cook 3-way splitting, recursing into Karatsuba below the Toom-3 threshold.
+    pub fn mul_toom3(&self, other: &BigUint, thresholds: &MulThresholds) -> BigUint {
+        BigUint::from_limbs(toom3(&self.limbs, &other.limbs, thresholds))
+    }
+
+    // Multiplies using a number theoretic transform in O(n log n) limb operations.
+    pub fn mul_ntt(&self, other: &BigUint) -> BigUint {
+        BigUint::from_limbs(ntt_multiply(&self.limbs, &other.limbs))
+    }
+
+    // Multiplies with the algorithm best suited to the operand sizes.
+    pub fn mul_with(&self, other: &BigUint, thresholds: &MulThresholds) -> BigUint {
+        BigUint::from_limbs(multiply(&self.limbs, &other.limbs, thresholds))
+    }
+
+    // Writes the number in decimal.
+    fn to_decimal_string(&self) -> String {
+        if self.limbs.len() <= DECIMAL_SPLIT_THRESHOLD {
+            return decimal_schoolbook(&self.limbs);
+        }
+
+        // Powers 10^(9.2^i), until the square of the last one is bigger than the number.
+        let mut powers = vec![BigUint::from_u64(DECIMAL_BASE as u64)];
+        while powers.last().unwrap().limbs.len() * 2 - 1 <= self.limbs.len() {
+            let square = powers.last().unwrap() * powers.last().unwrap();
+            powers.push(square);
+        }
+
+        // Only the powers that split numbers too long for the 9 digit method are ever divided by.
+        let reciprocals = powers
+            .iter()
+            .map(|power| match power.limbs.len() * 2 > DECIMAL_SPLIT_THRESHOLD {
+                true => Some(reciprocal(power)),
+                false => None,
+            })
+            .collect::<Vec<Option<BigUint>>>();
+
+        let mut result = String::new();
+        write_decimal(self, powers.len() - 1, 0, &powers, &reciprocals, &mut result);
+        result
+    }
+}
+
+impl Ord for BigUint {
+    fn cmp(&self, other: &BigUint) -> Ordering {
+        cmp_slices(&self.limbs, &other.limbs)
+    }
+}
+
+impl PartialOrd for BigUint {
+    fn partial_cmp(&self, other: &BigUint) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Add for &BigUint {
+    type Output = BigUint;
+
+    fn add(self, other: &BigUint) -> BigUint {
+        BigUint::from_limbs(add_slices(&self.limbs, &other.limbs))
+    }
+}
+
+impl Sub for &BigUint {
+    type Output = BigUint;
+
+    // Panics if the result would be negative, just like primitive unsigned types.
+    fn sub(self, other: &BigUint) -> BigUint {
+        self.checked_sub(other).expect("attempt to subtract with overflow")
+    }
+}
+
+impl Mul for &BigUint {
+    type Output = BigUint;
+
+    fn mul(self, other: &BigUint) -> BigUint {
+        self.mul_with(other, &MulThresholds::default())
+    }
+}
+
+impl FromStr for BigUint {
+    type Err = ParseBigUintError;
+
+    // Parses hex if the string starts with "0x", decimal otherwise.
+    fn from_str(string: &str) -> Result<BigUint, ParseBigUintError> {
+        if string.starts_with("0x") {
+            BigUint::from_str_radix(string, 16)
+        } else {
+            BigUint::from_str_radix(string, 10)
+        }
+    }
+}
+
+impl fmt::Display for BigUint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad_integral(true, "", &self.to_decimal_string())
+    }
+}
+
+impl fmt::LowerHex for BigUint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut result = match self.limbs.last() {
+            Some(limb) => format!("{:x}", limb),
+            None => String::from("0"),
+        };
+        for limb in self.limbs.iter().rev().skip(1) {
+            result.push_str(&format!("{:08x}", limb));
+        }
+
+        f.pad_integral(true, "0x", &result)
+    }
+}
+
+// Removes the zero limbs from the most significant end.
+fn trim(limbs: &mut Vec<u32>) {
+    while limbs.last() == Some(&0) {
+        limbs.pop();
+    }
+}
+
+// Converts a short run of validated ascii digits into a limb.
+fn parse_chunk(digits: &[u8], radix: u32) -> u32 {
+    digits.iter().fold(0, |value, digit| {
+        value * radix + (*digit as char).to_digit(radix).unwrap()
+    })
+}
+
+// Compares two trimmed limb slices.
+fn cmp_slices(a: &[u32], b: &[u32]) -> Ordering {
+    if a.len() != b.len() {
+        return a.len().cmp(&b.len());
+    }
+
+    // Same length, first differing limb from the top decides.
+    a.iter().rev().cmp(b.iter().rev())
+}
+
+// Computes limbs * multiplier + addend in place.
+fn mul_small_add(limbs: &mut Vec<u32>, multiplier: u32, addend: u32) {
+    let mut carry = addend as u64;
+    for limb in limbs.iter_mut() {
+        let value = *limb as u64 * multiplier as u64 + carry;
+        *limb = value as u32;
+        carry = value >> 32;
+    }
+
+    if carry != 0 {
+        limbs.push(carry as u32);
+    }
+}
+
+// Divides limbs by divisor in place, and returns the remainder.
+fn div_small(limbs: &mut Vec<u32>, divisor: u32) -> u32 {
+    let mut remainder: u64 = 0;
+    for limb in limbs.iter_mut().rev() {
+        let value = (remainder << 32) | *limb as u64;
+        *limb = (value / divisor as u64) as u32;
+        remainder = value % divisor as u64;
+    }
+
+    trim(limbs);
+    remainder as u32
+}
+
+// Parses validated decimal digits, splitting long strings at 9.2^i digits from the right.
+fn parse_decimal(digits: &[u8], powers: &[BigUint]) -> BigUint {
+    if digits.len() <= DECIMAL_SPLIT_THRESHOLD * DECIMAL_BASE_DIGITS {
+        // Consume 9 digits at a time, starting with the leftover chunk on the left.
+        let mut limbs = Vec::new();
+        let mut start = 0;
+        let mut end = match digits.len() % DECIMAL_BASE_DIGITS {
+            0 => DECIMAL_BASE_DIGITS,
+            length => length,
+        };
+        while start < digits.len() {
+            let chunk = parse_chunk(&digits[start..end], 10);
+            mul_small_add(&mut limbs, 10_u32.pow((end - start) as u32), chunk);
+            start = end;
+            end += DECIMAL_BASE_DIGITS;
+        }
+        return BigUint::from_limbs(limbs);
+    }
+
+    // Largest power with fewer digits than the string, so the high part is never the longer one.
+    let level = (0..powers.len())
+        .rev()
+        .find(|&level| DECIMAL_BASE_DIGITS << level < digits.len())
+        .unwrap();
+    let (high, low) = digits.split_at(digits.len() - (DECIMAL_BASE_DIGITS << level));
+    &(&parse_decimal(high, powers) * &powers[level]) + &parse_decimal(low, powers)
+}
+
+// Writes limbs in decimal, peeling off 9 digits at a time from the right.
+fn decimal_schoolbook(limbs: &[u32]) -> String {
+    if limbs.is_empty() {
+        return String::from("0");
+    }
+
+    let mut limbs = limbs.to_vec();
+    let mut chunks = Vec::new();
+    while !limbs.is_empty() {
+        chunks.push(div_small(&mut limbs, DECIMAL_BASE));
+    }
+
+    let mut result = chunks.last().unwrap().to_string();
+    for chunk in chunks.iter().rev().skip(1) {
+        result.push_str(&format!("{:09}", chunk));
+    }
+
+    result
+}
+
+// Appends x < powers[level]^2 in decimal, padded with zeros to [width] digits.
+fn write_decimal(
+    x: &BigUint,
+    level: usize,
+    width: usize,
+    powers: &[BigUint],
+    reciprocals: &[Option<BigUint>],
+    result: &mut String,
+) {
+    if x.limbs.len() <= DECIMAL_SPLIT_THRESHOLD {
+        let digits = decimal_schoolbook(&x.limbs);
+        for _ in digits.len()..width {
+            result.push('0');
+        }
+        result.push_str(&digits);
+        return;
+    }
+
+    let (high, low) = div_rem_with_reciprocal(x, &powers[level], reciprocals[level].as_ref().unwrap());
+    let low_width = DECIMAL_BASE_DIGITS << level;
+
+    // No leading zeros at the front of the number.
+    if width > 0 || !high.is_zero() {
+        write_decimal(
+            &high,
+            level - 1,
+            width.saturating_sub(low_width),
+            powers,
+            reciprocals,
+            result,
+        );
+        write_decimal(&low, level - 1, low_width, powers, reciprocals, result);
+    } else {
+        write_decimal(&low, level - 1, 0, powers, reciprocals, result);
+    }
+}
+
+// Returns x.B^shift.
+fn shift_up(x: &BigUint, shift: usize) -> BigUint {
+    let mut limbs = vec![0; shift];
+    limbs.extend_from_slice(&x.limbs);
+    BigUint::from_limbs(limbs)
+}
+
+// Returns floor(x / B^shift).
+fn shift_down(x: &BigUint, shift: usize) -> BigUint {
+    BigUint::from_limbs(x.limbs[min(shift, x.limbs.len())..].to_vec())
+}
+
+// Returns r <= floor(B^2m / d) for d of m limbs, exact for m <= 6 and at most a few below otherwise.
+fn reciprocal(d: &BigUint) -> BigUint {
+    let m = d.limbs.len();
+    let top = |limbs: &[u32]| {
+        limbs
+            .iter()
+            .rev()
+            .fold(0u128, |value, &limb| value << 32 | limb as u128)
+    };
+    let from_u128 = |value: u128| BigUint::from_limbs((0..4).map(|index| (value >> (32 * index)) as u32).collect());
+
+    // Newton's iteration x + x.(B^2m - d.x) / B^2m doubles the correct digits, and never overshoots from below.
+    let power = shift_up(&BigUint::from_u64(1), 2 * m);
+    let newton_step = |x: &BigUint| {
+        let error = &power - &(d * x);
+        (shift_down(&(x * &error), 2 * m), error)
+    };
+
+    if m > 6 {
+        // Reciprocal of the top h limbs, scaled up, is at most a factor 1 + B^-(h - 1) above the answer, so take that
+        // much off. Correct to about 2h - 2 limbs, one step then leaves it at most a few below.
+        let h = m / 2 + 3;
+        let estimate = shift_up(&reciprocal(&shift_down(d, m - h)), m - h);
+        let margin = &shift_down(&estimate, h - 1) + &BigUint::from_u64(2);
+        let x = estimate.checked_sub(&margin).unwrap_or_else(BigUint::zero);
+        return &x + &newton_step(&x).0;
+    }
+
+    let mut x = if m <= 2 {
+        // Off by at most one.
+        let numerator = if m == 1 { u64::MAX as u128 } else { u128::MAX };
+        from_u128(numerator / top(&d.limbs))
+    } else {
+        // d < (t + 1).B^(m - 2) for its top two limbs t, correct to 32 bits.
+        shift_up(&from_u128(u128::MAX / (top(&d.limbs[m - 2..]) + 1)), m - 2)
+    };
+
+    // Step until exact, at least one at a time.
+    loop {
+        let (step, error) = newton_step(&x);
+        if error < *d {
+            return x;
+        }
+        x = &x + &max(step, BigUint::from_u64(1));
+    }
+}
+
+// Returns (x / d, x mod d) for x < B^2m, where d has m limbs and r = reciprocal(d).
+fn div_rem_with_reciprocal(x: &BigUint, d: &BigUint, r: &BigUint) -> (BigUint, BigUint) {
+    // Estimate from the top m + 1 limbs of x is at most the quotient, and only a few below.
+    let m = d.limbs.len();
+    let mut quotient = shift_down(&(&shift_down(x, m - 1) * r), m + 1);
+    let mut remainder = x - &(&quotient * d);
+    while remainder >= *d {
+        remainder = &remainder - d;
+        quotient = &quotient + &BigUint::from_u64(1);
+    }
+    (quotient, remainder)
+}
+
+// Returns a + b.
+fn add_slices(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut result = vec![0; max(a.len(), b.len()) + 1];
+    result[..a.len()].copy_from_slice(a);
+    add_in_place(&mut result, b);
+    trim(&mut result);
+    result
+}
+
+// Adds b into acc. Acc must have enough room for the final carry.
+fn add_in_place(acc: &mut [u32], b: &[u32]) {
+    let mut carry: u64 = 0;
+    for (index, limb) in b.iter().enumerate() {
+        let value = acc[index] as u64 + *limb as u64 + carry;
+        acc[index] = value as u32;
+        carry = value >> 32;
+    }
+
+    // Ripple the carry into the higher limbs.
+    let mut index = b.len();
+    while carry != 0 {
+        let value = acc[index] as u64 + carry;
+        acc[index] = value as u32;
+        carry = value >> 32;
+        index += 1;
+    }
+}
+
+// Subtracts b from acc. Acc must not be smaller than b.
+fn sub_in_place(acc: &mut [u32], b: &[u32]) {
+    let mut borrow: i64 = 0;
+    for (index, limb) in b.iter().enumerate() {
+        let value = acc[index] as i64 - *limb as i64 - borrow;
+        acc[index] = value as u32;
+        borrow = (value < 0) as i64;
+    }
+
+    // Ripple the borrow into the higher limbs.
+    let mut index = b.len();
+    while borrow != 0 {
+        let value = acc[index] as i64 - borrow;
+        acc[index] = value as u32;
+        borrow = (value < 0) as i64;
+        index += 1;
+    }
+}
+
+// Grade school multiplication of limb slices.
+fn schoolbook(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut result = vec![0; a.len() + b.len()];
+    for (i, x) in a.iter().enumerate() {
+        // Largest possible value is (2^32 - 1)^2 + 2 * (2^32 - 1) = 2^64 - 1, so this never overflows.
+        let mut carry: u64 = 0;
+        for (j, y) in b.iter().enumerate() {
+            let value = result[i + j] as u64 + *x as u64 * *y as u64 + carry;
+            result[i + j] = value as u32;
+            carry = value >> 32;
+        }
+        result[i + b.len()] = carry as u32;
+    }
+
+    trim(&mut result);
+    result
+}
+
+// Karatsuba multiplication of limb slices. Result is always trimmed.
+//
+// For a = a1.B^h + a0 and b = b1.B^h + b0,
+// a.b = a1.b1.B^2h + ((a0 + a1).(b0 + b1) - a0.b0 - a1.b1).B^h + a0.b0
+fn karatsuba(a: &[u32], b: &[u32], threshold: usize) -> Vec<u32> {
+    // Small enough to multiply directly. At least 2 limbs are needed for the split to make progress.
+    if min(a.len(), b.len()) < max(threshold, 2) {
+        return schoolbook(a, b);
+    }
+
+    // Split both numbers at half of the longer one.
+    let half = max(a.len(), b.len()) / 2;
+    let (a0, a1) = a.split_at(min(half, a.len()));
+    let (b0, b1) = b.split_at(min(half, b.len()));
+
+    // Recursively compute a0.b0, a1.b1 and (a0 + a1).(b0 + b1).
+    let z0 = karatsuba(a0, b0, threshold);
+    let z2 = karatsuba(a1, b1, threshold);
+    let mut z1 = karatsuba(&add_slices(a0, a1), &add_slices(b0, b1), threshold);
+
+    // Gauss's trick, the middle term is obtained with subtractions instead of two more multiplications.
+    sub_in_place(&mut z1, &z0);
+    sub_in_place(&mut z1, &z2);
+    trim(&mut z1);
+
+    // Put the three parts together. One spare limb absorbs the carries of the partial sums.
+    let mut result = vec![0; a.len() + b.len() + 1];
+    add_in_place(&mut result, &z0);
+    add_in_place(&mut result[half..], &z1);
+    add_in_place(&mut result[2 * half..], &z2);
+
+    trim(&mut result);
+    result
+}
+
+// Picks the multiplication algorithm by the size of the shorter operand.
+fn multiply(a: &[u32], b: &[u32], thresholds: &MulThresholds) -> Vec<u32> {
+    let length = min(a.len(), b.len());
+    if length >= thresholds.ntt {
+        ntt_multiply(a, b)
+    } else if length >= thresholds.toom3 {
+        toom3(a, b, thresholds)
+    } else {
+        karatsuba(a, b, thresholds.karatsuba)
+    }
+}
+
+// Signed number, since Toom-3 evaluates the operands at negative points.
+#[derive(Debug, Clone)]
+struct Signed {
+    negative: bool,
+    // Trimmed magnitude.
+    magnitude: Vec<u32>,
+}
+
+impl Signed {
+    // Wraps a non negative limb slice.
+    fn from_slice(limbs: &[u32]) -> Signed {
+        let mut magnitude = limbs.to_vec();
+        trim(&mut magnitude);
+        Signed {
+            negative: false,
+            magnitude,
+        }
+    }
+
+    // Builds a number, making sure zero is never negative.
+    fn new(negative: bool, magnitude: Vec<u32>) -> Signed {
+        Signed {
+            negative: negative && !magnitude.is_empty(),
+            magnitude,
+        }
+    }
+
+    fn add(&self, other: &Signed) -> Signed {
+        // Same signs, magnitudes add up.
+        if self.negative == other.negative {
+            return Signed::new(self.negative, add_slices(&self.magnitude, &other.magnitude));
+        }
+
+        // Different signs, the larger magnitude decides the sign.
+        let (larger, smaller) = match cmp_slices(&self.magnitude, &other.magnitude) {
+            Ordering::Less => (other, self),
+            _ => (self, other),
+        };
+        let mut magnitude = larger.magnitude.clone();
+        sub_in_place(&mut magnitude, &smaller.magnitude);
+        trim(&mut magnitude);
+        Signed::new(larger.negative, magnitude)
+    }
+
+    fn sub(&self, other: &Signed) -> Signed {
+        self.add(&Signed::new(!other.negative, other.magnitude.clone()))
+    }
+
+    fn mul(&self, other: &Signed, thresholds: &MulThresholds) -> Signed {
+        Signed::new(
+            self.negative != other.negative,
+            toom3(&self.magnitude, &other.magnitude, thresholds),
+        )
+    }
+
+    fn mul_small(&self, multiplier: u32) -> Signed {
+        let mut magnitude = self.magnitude.clone();
+        mul_small_add(&mut magnitude, multiplier, 0);
+        Signed::new(self.negative, magnitude)
+    }
+
+    // Divides by a number that is known to divide it exactly.
+    fn div_exact(&self, divisor: u32) -> Signed {
+        let mut magnitude = self.magnitude.clone();
+        let remainder = div_small(&mut magnitude, divisor);
+        debug_assert_eq!(remainder, 0, "Toom-3 interpolation division is not exact");
+        Signed::new(self.negative, magnitude)
+    }
+}
+
+// Toom-Cook 3-way multiplication of limb slices. Result is always trimmed.
+//
+// Each operand is split into three parts, a = a2.B^2k + a1.B^k + a0, and seen as a polynomial in B^k. The product
+// polynomial has degree 4, so it is found from its values at 5 points (0, 1, -1, -2, infinity), which needs only 5
+// recursive multiplications instead of the 9 of schoolbook splitting.
+fn toom3(a: &[u32], b: &[u32], thresholds: &MulThresholds) -> Vec<u32> {
+    if min(a.len(), b.len()) < max(thresholds.toom3, 3) {
+        return karatsuba(a, b, thresholds.karatsuba);
+    }
+
+    // Split both numbers into thirds of the longer one.
+    let k = max(a.len(), b.len()).div_ceil(3);
+    let (a0, a1, a2) = split_in_three(a, k);
+    let (b0, b1, b2) = split_in_three(b, k);
+
+    // Evaluate both polynomials at 0, 1, -1, -2 and infinity.
+    let evaluate = |x0: &Signed, x1: &Signed, x2: &Signed| {
+        let p0 = x0.add(x2);
+        let p1 = p0.add(x1);
+        let p_minus_1 = p0.sub(x1);
+        let p_minus_2 = p_minus_1.add(x2).mul_small(2).sub(x0);
+        [x0.clone(), p1, p_minus_1, p_minus_2, x2.clone()]
+    };
+    let points_a = evaluate(&a0, &a1, &a2);
+    let points_b = evaluate(&b0, &b1, &b2);
+
+    // Pointwise multiplication gives the values of the product polynomial.
+    let mut values = Vec::with_capacity(5);
+    for (x, y) in points_a.iter().zip(points_b.iter()) {
+        values.push(x.mul(y, thresholds));
+    }
+    let (r0, r1, r_minus_1, r_minus_2, r_infinity) = (&values[0], &values[1], &values[2], &values[3], &values[4]);
+
+    // Interpolate the coefficients back (Bodrato's sequence).
+    let mut c3 = r_minus_2.sub(r1).div_exact(3);
+    let mut c1 = r1.sub(r_minus_1).div_exact(2);
+    let mut c2 = r_minus_1.sub(r0);
+    c3 = c2.sub(&c3).div_exact(2).add(&r_infinity.mul_small(2));
+    c2 = c2.add(&c1).sub(r_infinity);
+    c1 = c1.sub(&c3);
+
+    // All coefficients of the product are sums of non negative products, so they are never negative.
+    let mut result = vec![0; a.len() + b.len() + 1];
+    for (index, coefficient) in [r0, &c1, &c2, &c3, r_infinity].iter().enumerate() {
+        debug_assert!(!coefficient.negative, "Toom-3 coefficient can't be negative");
+        if !coefficient.magnitude.is_empty() {
+            add_in_place(&mut result[index * k..], &coefficient.magnitude);
+        }
+    }
+
+    trim(&mut result);
+    result
+}
+
+// Splits limbs into three parts of k limbs, the last part may be shorter or empty.
+fn split_in_three(limbs: &[u32], k: usize) -> (Signed, Signed, Signed) {
+    let first = min(k, limbs.len());
+    let second = min(2 * k, limbs.len());
+    (
+        Signed::from_slice(&limbs[..first]),
+        Signed::from_slice(&limbs[first..second]),
+        Signed::from_slice(&limbs[second..]),
+    )
+}
+
+// Returns (a + b) mod p.
+fn mod_add(a: u64, b: u64) -> u64 {
+    let (sum, overflow) = a.overflowing_add(b);
+
+    // Overflowing dropped 2^64, which is 2^32 - 1 mod p.
+    let sum = if overflow { sum + NTT_EPSILON } else { sum };
+    if sum >= NTT_PRIME {
+        sum - NTT_PRIME
+    } else {
+        sum
+    }
+}
+
+// Returns (a - b) mod p.
+fn mod_sub(a: u64, b: u64) -> u64 {
+    if a >= b {
+        a - b
+    } else {
+        NTT_PRIME - b + a
+    }
+}
+
+// Returns (a * b) mod p.
+fn mod_mul(a: u64, b: u64) -> u64 {
+    let product = a as u128 * b as u128;
+    let low = product as u64;
+    let high = (product >> 64) as u64;
+
+    // product = low + high_low.2^64 + high_high.2^96, where 2^64 = 2^32 - 1 and 2^96 = -1 (mod p).
+    let high_high = high >> 32;
+    let high_low = high & NTT_EPSILON;
+
+    let (mut result, borrow) = low.overflowing_sub(high_high);
+    if borrow {
+        // Borrowed 2^64, give back 2^32 - 1. Can't underflow since result is at least 2^64 - 2^32 here.
+        result -= NTT_EPSILON;
+    }
+
+    let (sum, carry) = result.overflowing_add(high_low * NTT_EPSILON);
+    result = if carry { sum + NTT_EPSILON } else { sum };
+
+    if result >= NTT_PRIME {
+        result - NTT_PRIME
+    } else {
+        result
+    }
+}
+
+// Returns (base ^ exponent) mod p.
+fn mod_pow(mut base: u64, mut exponent: u64) -> u64 {
+    let mut result = 1;
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = mod_mul(result, base);
+        }
+        base = mod_mul(base, base);
+        exponent >>= 1;
+    }
+
+    result
+}
+
+// In place iterative number theoretic transform. Length must be a power of two.
+fn ntt(values: &mut [u64], invert: bool) {
+    let n = values.len();
+
+    // Reorder values by bit reversed index, so butterflies can work bottom up.
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j ^= bit;
+
+        if i < j {
+            values.swap(i, j);
+        }
+    }
+
+    // Combine transforms of length / 2 into transforms of length.
+    let mut length = 2;
+    while length <= n {
+        let mut root = mod_pow(NTT_GENERATOR, (NTT_PRIME - 1) / length as u64);
+        if invert {
+            root = mod_pow(root, NTT_PRIME - 2);
+        }
+
+        // Powers of the root of unity for this level.
+        let half = length / 2;
+        let mut twiddles = Vec::with_capacity(half);
+        let mut twiddle = 1;
+        for _ in 0..half {
+            twiddles.push(twiddle);
+            twiddle = mod_mul(twiddle, root);
+        }
+
+        for start in (0..n).step_by(length) {
+            for offset in 0..half {
+                let u = values[start + offset];
+                let v = mod_mul(values[start + offset + half], twiddles[offset]);
+                values[start + offset] = mod_add(u, v);
+                values[start + offset + half] = mod_sub(u, v);
+            }
+        }
+
+        length <<= 1;
+    }
+
+    // Inverse transform needs to be scaled down by n.
+    if invert {
+        let n_inverse = mod_pow(n as u64, NTT_PRIME - 2);
+        for value in values.iter_mut() {
+            *value = mod_mul(*value, n_inverse);
+        }
+    }
+}
+
+// Multiplies limb slices by convolving their 16 bit digits with a number theoretic transform.
+fn ntt_multiply(a: &[u32], b: &[u32]) -> Vec<u32> {
+    if a.is_empty() || b.is_empty() {
+        return Vec::new();
+    }
+
+    // Each convolution sum is below min(length) * 2^32, which has to stay below the prime.
+    let digits_a = to_ntt_digits(a);
+    let digits_b = to_ntt_digits(b);
+    let length = (digits_a.len() + digits_b.len()).next_power_of_two();
+    assert!(length <= 1 << 32, "Numbers are too large for the transform");
+
+    let mut values_a = digits_a;
+    let mut values_b = digits_b;
+    values_a.resize(length, 0);
+    values_b.resize(length, 0);
+
+    // Convolution is a pointwise product in the transformed domain.
+    ntt(&mut values_a, false);
+    ntt(&mut values_b, false);
+    for (x, y) in values_a.iter_mut().zip(values_b.iter()) {
+        *x = mod_mul(*x, *y);
+    }
+    ntt(&mut values_a, true);
+
+    // Propagate carries, and pack two 16 bit digits back into every limb.
+    let mask = (1 << NTT_DIGIT_BITS) - 1;
+    let mut result = Vec::with_capacity(length / 2);
+    let mut carry: u128 = 0;
+    for pair in values_a.chunks(2) {
+        let mut limb = 0;
+        for (index, value) in pair.iter().enumerate() {
+            let total = *value as u128 + carry;
+            limb |= ((total & mask) as u32) << (index as u32 * NTT_DIGIT_BITS);
+            carry = total >> NTT_DIGIT_BITS;
+        }
+        result.push(limb);
+    }
+    debug_assert_eq!(carry, 0, "Product does not fit in the transform");
+
+    trim(&mut result);
+    result
+}
+
+// Splits every limb into two 16 bit digits.
+fn to_ntt_digits(limbs: &[u32]) -> Vec<u64> {
+    let mask = (1 << NTT_DIGIT_BITS) - 1;
+    let mut digits = Vec::with_capacity(2 * limbs.len());
+    for limb in limbs {
+        digits.push((limb & mask) as u64);
+        digits.push((limb >> NTT_DIGIT_BITS) as u64);
+    }
+
+    digits
+}
+
+fn main() {
+    let x: BigUint = "123456789012345678901234567890".parse().unwrap();
+    let y = BigUint::from_str_radix("0xffffffffffffffffffffffff", 16).unwrap();
+
+    println!("x = {}, y = {:#x}", x, y);
+    println!("x + y = {}", &x + &y);
+    println!("x - y = {:?}", x.checked_sub(&y).map(|value| value.to_string()));
+    println!("y - x = {:?}", y.checked_sub(&x).map(|value| value.to_string()));
+    println!("x * y = {}", &x * &y);
+
+    // All multiplication algorithms agree.
+    let thresholds = MulThresholds::default();
+    println!("Toom-3: x * y = {}", x.mul_toom3(&y, &thresholds));
+    println!("NTT: x * y = {}", x.mul_ntt(&y));
+
+    // Split decimal conversion agrees with the 9 digit method, on pseudo random limbs and on powers of 10 around the
+    // split points.
+    for length in (1..400).chain((400..6000).step_by(397)) {
+        let limbs = (0..length as u64)
+            .map(|index| ((index + 1).wrapping_mul(0x9e37_79b9_7f4a_7c15) >> 32) as u32)
+            .collect::<Vec<u32>>();
+        let number = BigUint::from_limbs(limbs);
+        let decimal = number.to_string();
+        assert_eq!(
+            decimal,
+            decimal_schoolbook(&number.limbs),
+            "Decimal of {} limbs differs",
+            length
+        );
+        assert_eq!(decimal.parse::<BigUint>().unwrap(), number);
+
+        let power = format!("1{}", "0".repeat(length * 9));
+        let parsed = power.parse::<BigUint>().unwrap();
+        assert_eq!(parsed.to_string(), power);
+        assert_eq!((&parsed - &BigUint::from_u64(1)).to_string(), "9".repeat(length * 9));
+    }
+    println!("Decimal conversion verified up to 6000 limbs");
+}
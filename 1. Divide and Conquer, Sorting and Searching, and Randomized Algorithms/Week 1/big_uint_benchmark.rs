@@ -0,0 +1,218 @@
+/**
+ * Benchmark for the big integer multiplication algorithms.
+ *
+ * For growing operand sizes, times one level of each algorithm on top of the algorithm below it, and prints the
+ * sizes from which it starts winning on this machine. Timings are noisy, so the thresholds in big_uint.rs are the
+ * crossovers that come up over several seeds rather than the output of a single run.
+ */
+use std::env;
+use std::time::{Duration, Instant};
+
+#[allow(dead_code)]
+mod big_uint;
+use big_uint::{BigUint, MulThresholds, KARATSUBA_THRESHOLD};
+
+#[allow(dead_code)]
+#[path = "../Week 3/rng.rs"]
+mod rng;
+use rng::Rng;
+
+// Keep running an algorithm until this much time has passed, and keep the fastest run to filter out noise.
+const MIN_SAMPLE_TIME: Duration = Duration::from_millis(50);
+
+// Stop timing an algorithm once a single run takes this long.
+const MAX_RUN_TIME: Duration = Duration::from_secs(1);
+
+// Approximate number of decimal digits in a limb (32 * log10(2)).
+const DIGITS_PER_LIMB: f64 = 9.633;
+
+// Returns a random number with exactly [length] limbs.
+fn random_number(length: usize, rng: &mut Rng) -> BigUint {
+    let mut limbs = Vec::with_capacity(length);
+    for _ in 0..length {
+        limbs.push(rng.next_u64() as u32);
+    }
+
+    // Make sure the top limb is not zero.
+    if let Some(last) = limbs.last_mut() {
+        *last |= 1;
+    }
+
+    BigUint::from_limbs(limbs)
+}
+
+// Returns the fastest running time of [multiply], along with its result.
+fn time<F: Fn() -> BigUint>(multiply: F) -> (Duration, BigUint) {
+    let start = Instant::now();
+    let mut fastest = Duration::MAX;
+    loop {
+        let run_start = Instant::now();
+        let result = multiply();
+        fastest = fastest.min(run_start.elapsed());
+
+        if start.elapsed() >= MIN_SAMPLE_TIME {
+            return (fastest, result);
+        }
+    }
+}
+
+// Returns the first size from which the challenger is always faster than the baseline, if any.
+fn crossover(sizes: &[usize], baseline: &[Option<Duration>], challenger: &[Option<Duration>]) -> Option<usize> {
+    let mut result = None;
+    for index in 0..sizes.len() {
+        match (baseline[index], challenger[index]) {
+            (Some(baseline), Some(challenger)) if challenger < baseline => {
+                if result.is_none() {
+                    result = Some(sizes[index]);
+                }
+            }
+            // Challenger wins by default once the baseline is too slow to measure.
+            (None, Some(_)) => {
+                if result.is_none() {
+                    result = Some(sizes[index]);
+                }
+            }
+            // Neither could be measured, nothing to learn.
+            (None, None) => {}
+            _ => result = None,
+        }
+    }
+
+    result
+}
+
+// Formats a time column entry.
+fn format_time(duration: Option<Duration>) -> String {
+    match duration {
+        Some(duration) => format!("{:.1}", duration.as_secs_f64() * 1e6),
+        None => String::from("-"),
+    }
+}
+
+// Prints the crossover for one algorithm.
+fn print_crossover(name: &str, baseline: &str, size: Option<usize>) {
+    match size {
+        Some(size) => println!(
+            "{} beats {} from {} limbs (~{} digits)",
+            name,
+            baseline,
+            size,
+            (size as f64 * DIGITS_PER_LIMB) as usize
+        ),
+        None => println!("{} does not beat {} at the measured sizes", name, baseline),
+    }
+}
+
+fn main() {
+    // Ensure proper usage.
+    let mut args = env::args().skip(1).collect::<Vec<String>>();
+    let mut rng = match Rng::from_args(&mut args) {
+        Some(rng) if args.len() <= 1 => rng,
+        _ => {
+            eprintln!("usage: ./big_uint_benchmark [max limbs] [--seed <seed>]");
+            std::process::exit(1);
+        }
+    };
+
+    let max_limbs = match args.first() {
+        Some(arg) => arg.parse::<usize>().expect("Failed to parse max limbs"),
+        None => 1 << 16,
+    };
+
+    // Sizes grow by a factor of ~1.5, to see the crossovers with a reasonable resolution.
+    let mut sizes = Vec::new();
+    let mut size = 8;
+    while size <= max_limbs {
+        sizes.push(size);
+        if size * 3 / 2 <= max_limbs {
+            sizes.push(size * 3 / 2);
+        }
+        size *= 2;
+    }
+
+    let defaults = MulThresholds::default();
+
+    // Times in each column, None once the algorithm got too slow.
+    let mut schoolbook_times = Vec::new();
+    let mut karatsuba_times = Vec::new();
+    let mut full_karatsuba_times = Vec::new();
+    let mut toom3_times = Vec::new();
+    let mut best_times = Vec::new();
+    let mut ntt_times = Vec::new();
+
+    println!(
+        "{:>8} {:>10} {:>14} {:>14} {:>14} {:>14} {:>14} {:>14}",
+        "limbs", "digits", "schoolbook us", "karatsuba1 us", "karatsuba us", "toom3_1 us", "below ntt us", "ntt us"
+    );
+
+    for &size in &sizes {
+        let x = random_number(size, &mut rng);
+        let y = random_number(size, &mut rng);
+
+        // One level of Karatsuba on top of schoolbook, full Karatsuba, one level of Toom-3 on top of Karatsuba, and
+        // the best of the algorithms below the transform.
+        let one_karatsuba = size;
+        let one_toom3 = MulThresholds {
+            toom3: size,
+            ..defaults
+        };
+        let no_ntt = MulThresholds {
+            ntt: usize::MAX,
+            ..defaults
+        };
+
+        let mut results = Vec::new();
+        let mut measure = |column: &mut Vec<Option<Duration>>, multiply: &dyn Fn() -> BigUint| {
+            // Skip algorithms that were already too slow on a smaller size.
+            if column.last() == Some(&None) {
+                column.push(None);
+                return;
+            }
+
+            let (duration, result) = time(multiply);
+            results.push(result);
+            column.push(if duration < MAX_RUN_TIME { Some(duration) } else { None });
+        };
+
+        measure(&mut schoolbook_times, &|| x.mul_schoolbook(&y));
+        measure(&mut karatsuba_times, &|| x.mul_karatsuba(&y, one_karatsuba));
+        measure(&mut full_karatsuba_times, &|| x.mul_karatsuba(&y, KARATSUBA_THRESHOLD));
+        measure(&mut toom3_times, &|| x.mul_toom3(&y, &one_toom3));
+        measure(&mut best_times, &|| x.mul_with(&y, &no_ntt));
+        measure(&mut ntt_times, &|| x.mul_ntt(&y));
+
+        // Every algorithm has to agree on the product.
+        for result in &results {
+            assert_eq!(
+                *result, results[0],
+                "Multiplication algorithms disagree at {} limbs",
+                size
+            );
+        }
+
+        println!(
+            "{:>8} {:>10} {:>14} {:>14} {:>14} {:>14} {:>14} {:>14}",
+            size,
+            (size as f64 * DIGITS_PER_LIMB) as usize,
+            format_time(*schoolbook_times.last().unwrap()),
+            format_time(*karatsuba_times.last().unwrap()),
+            format_time(*full_karatsuba_times.last().unwrap()),
+            format_time(*toom3_times.last().unwrap()),
+            format_time(*best_times.last().unwrap()),
+            format_time(*ntt_times.last().unwrap()),
+        );
+    }
+
+    println!();
+    print_crossover(
+        "Karatsuba",
+        "schoolbook",
+        crossover(&sizes, &schoolbook_times, &karatsuba_times),
+    );
+    print_crossover(
+        "Toom-3",
+        "Karatsuba",
+        crossover(&sizes, &full_karatsuba_times, &toom3_times),
+    );
+    print_crossover("NTT", "Karatsuba/Toom-3", crossover(&sizes, &best_times, &ntt_times));
+}
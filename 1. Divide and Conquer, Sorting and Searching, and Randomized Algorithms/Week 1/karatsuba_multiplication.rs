@@ -1,44 +1,114 @@
 /**
  * Karatsuba Multiplication.
  *
- * For two numbers x and y,
- * x.y = (10^(n / 2).a + b) . (10^(n / 2).c + d)
+ * For two numbers x and y split at half their length,
+ * x.y = (B^(n / 2).a + b) . (B^(n / 2).c + d) = B^n.ac + B^(n / 2).((a + b).(c + d) - ac - bd) + bd
+ *
+ * Numbers can have any number of digits, see big_uint.rs for the arbitrary precision integers.
 **/
-use std::cmp::min;
-
-// Multiplies two numbers and return the result.
-fn multiply(num1: u128, num2: u128) -> u128 {
-    // Get the minimum length of the two numbers.
-    let length = min(num1.to_string().len(), num2.to_string().len()) as u32;
+use std::env;
+use std::time::Instant;
 
-    // Multiply it directly if any of the two numbers is single digit.
-    if length == 1 {
-        return num1 * num2;
-    }
+#[allow(dead_code)]
+mod big_uint;
+use big_uint::{BigUint, MulThresholds, KARATSUBA_THRESHOLD};
 
-    // Split number into two parts.
-    let denominator = (10 as i32).pow(length / 2) as u128;
-    let a = num1 / denominator;
-    let b = num1 % denominator;
-    let c = num2 / denominator;
-    let d = num2 % denominator;
+#[allow(dead_code)]
+#[path = "../Week 3/rng.rs"]
+mod rng;
+use rng::Rng;
 
-    // Recursively compute ac, bd and (a + b).(c + b).
-    let ac = multiply(a, c);
-    let bd = multiply(b, d);
+// Number of digits in the random operands used to verify Karatsuba.
+const VERIFY_DIGITS: usize = 10000;
+const VERIFY_ROUNDS: usize = 5;
 
-    // This is done so that we don't have to recursively compute ad + bc separately (Gauss trick)
-    let aplusb_cplusd = multiply(a + b, c + d);
-    let ad_plus_bc = aplusb_cplusd - ac - bd;
+// Returns a random decimal string of [length] digits with no leading zeros.
+fn random_digits(length: usize, rng: &mut Rng) -> String {
+    let mut digits = String::with_capacity(length);
+    for index in 0..length {
+        let digit = match index {
+            0 => rng.gen_range(1..10),
+            _ => rng.gen_range(0..10),
+        };
+        digits.push((b'0' + digit as u8) as char);
+    }
 
-    // Apply results into Karatsuba'a equation.
-    return 10_u128.pow(length) * ac + 10_u128.pow(length / 2) * ad_plus_bc + bd;
+    digits
 }
 
 fn main() {
-    let num1 = 12345678;
-    let num2 = 87654321;
-    let result = multiply(num1, num2);
+    // Ensure proper usage.
+    let mut args = env::args().skip(1).collect::<Vec<String>>();
+    let mut rng = match Rng::from_args(&mut args) {
+        Some(rng) if args.is_empty() || args.len() == 2 => rng,
+        _ => {
+            eprintln!("usage: ./karatsuba_multiplication [num1 num2] [--seed <seed>]");
+            std::process::exit(1);
+        }
+    };
+
+    // Multiply the given numbers (decimal, or hex with 0x prefix).
+    let (num1, num2) = match args.len() {
+        2 => (args[0].parse::<BigUint>(), args[1].parse::<BigUint>()),
+        _ => ("12345678".parse::<BigUint>(), "87654321".parse::<BigUint>()),
+    };
+    let (num1, num2) = match (num1, num2) {
+        (Ok(num1), Ok(num2)) => (num1, num2),
+        (Err(error), _) | (_, Err(error)) => {
+            eprintln!("Failed to parse number: {}", error);
+            std::process::exit(1);
+        }
+    };
+
+    println!("{} * {} = {}", num1, num2, &num1 * &num2);
+
+    // Check Karatsuba, Toom-3 and NTT against schoolbook multiplication on large random numbers.
+    let thresholds = MulThresholds::default();
+    println!("Seed = {}", rng.seed());
+    for round in 0..VERIFY_ROUNDS {
+        let x: BigUint = random_digits(VERIFY_DIGITS, &mut rng).parse().unwrap();
+        let y: BigUint = random_digits(VERIFY_DIGITS, &mut rng).parse().unwrap();
+
+        let karatsuba = x.mul_karatsuba(&y, KARATSUBA_THRESHOLD);
+        let schoolbook = x.mul_schoolbook(&y);
+        assert_eq!(karatsuba, schoolbook, "Karatsuba and schoolbook results differ");
+        assert_eq!(
+            x.mul_toom3(&y, &thresholds),
+            schoolbook,
+            "Toom-3 and schoolbook results differ"
+        );
+        assert_eq!(x.mul_ntt(&y), schoolbook, "NTT and schoolbook results differ");
+
+        // Round trip through decimal as well.
+        assert_eq!(karatsuba.to_string().parse::<BigUint>().unwrap(), karatsuba);
+
+        println!(
+            "Round {}: {} digit product verified against schoolbook",
+            round,
+            karatsuba.to_string().len()
+        );
+    }
 
-    println!("{} * {} = {}", num1, num2, result);
+    // Decimal input and output of large numbers should not take longer than multiplying them.
+    let digits = 1_000_000;
+    let (x, y) = (random_digits(digits, &mut rng), random_digits(digits, &mut rng));
+    let start = Instant::now();
+    let (a, b) = (x.parse::<BigUint>().unwrap(), y.parse::<BigUint>().unwrap());
+    let parse_time = start.elapsed();
+    let start = Instant::now();
+    let product = &a * &b;
+    let multiply_time = start.elapsed();
+    let start = Instant::now();
+    let decimal = product.to_string();
+    let print_time = start.elapsed();
+    assert_eq!(a.to_string(), x);
+    assert_eq!(decimal.parse::<BigUint>().unwrap(), product);
+    println!(
+        "{} digit operands: parsed in {:.2?}, multiplied in {:.2?}, {} digit product printed in {:.2?}",
+        digits,
+        parse_time,
+        multiply_time,
+        decimal.len(),
+        print_time
+    );
 }
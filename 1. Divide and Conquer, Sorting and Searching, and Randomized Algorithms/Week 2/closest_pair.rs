@@ -0,0 +1,394 @@
+/**
+ * Computational Geometry: Closest pair in 2D plane.
+ *
+ * Divide and conquer on points sorted by x, merging the halves by y on the way back up (like merge sort), so the
+ * whole search takes O(n log n). Across the dividing line only points closer to it than the best distance so far
+ * matter, and each of them only needs to be compared with the few that follow it by y.
+ *
+ * The k closest pairs come from the same recursion, keeping the k best pairs in a max heap and using the kth
+ * distance as the bound. Duplicate points make pairs at distance 0. Coordinates must not be NaN.
+ *
+ * Nearest neighbour of every point uses a k-d tree instead, split at the median of the axis with the wider spread,
+ * so lines of points with equal coordinates stay fast.
+ */
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::time::Instant;
+use std::{env, process};
+
+#[allow(dead_code)]
+mod point;
+use point::Point;
+
+#[allow(dead_code)]
+#[path = "../Week 3/rng.rs"]
+mod rng;
+use rng::Rng;
+
+// Pair of point indices, the smaller one first. Pairs order by distance, then indices.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Pair {
+    pub first: usize,
+    pub second: usize,
+    pub squared_distance: f64,
+}
+
+impl Pair {
+    fn new(points: &[Point], a: usize, b: usize) -> Pair {
+        Pair {
+            first: a.min(b),
+            second: a.max(b),
+            squared_distance: points[a].squared_euclidean_distance(&points[b]),
+        }
+    }
+
+    pub fn distance(&self) -> f64 {
+        self.squared_distance.sqrt()
+    }
+}
+
+impl Eq for Pair {}
+
+impl Ord for Pair {
+    fn cmp(&self, other: &Pair) -> Ordering {
+        self.squared_distance
+            .total_cmp(&other.squared_distance)
+            .then((self.first, self.second).cmp(&(other.first, other.second)))
+    }
+}
+
+impl PartialOrd for Pair {
+    fn partial_cmp(&self, other: &Pair) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// The k closest pairs found so far.
+struct Best {
+    k: usize,
+    heap: BinaryHeap<Pair>,
+}
+
+impl Best {
+    // Squared distance a new pair has to beat.
+    fn bound(&self) -> f64 {
+        match self.heap.peek() {
+            Some(pair) if self.heap.len() == self.k => pair.squared_distance,
+            _ => f64::INFINITY,
+        }
+    }
+
+    fn offer(&mut self, pair: Pair) {
+        if self.heap.len() < self.k {
+            self.heap.push(pair);
+        } else if pair.squared_distance < self.bound() {
+            self.heap.pop();
+            self.heap.push(pair);
+        }
+    }
+}
+
+// Returns the closest pair of points, or None if there are fewer than two.
+pub fn closest_pair(points: &[Point]) -> Option<Pair> {
+    k_closest_pairs(points, 1).pop()
+}
+
+// Returns the k closest pairs of points, closest first. Pairs tied with the kth distance may be left out.
+pub fn k_closest_pairs(points: &[Point], k: usize) -> Vec<Pair> {
+    if k == 0 || points.len() < 2 {
+        return Vec::new();
+    }
+
+    let mut indices = (0..points.len()).collect::<Vec<usize>>();
+    indices.sort_unstable_by(|&a, &b| points[a].x.total_cmp(&points[b].x));
+
+    // Position of every point by x, telling which half of a split it falls in.
+    let mut ranks = vec![0; points.len()];
+    for (rank, &index) in indices.iter().enumerate() {
+        ranks[index] = rank;
+    }
+
+    let mut best = Best {
+        k,
+        heap: BinaryHeap::with_capacity(k + 1),
+    };
+    let mut buffer = Vec::with_capacity(points.len());
+    search(points, &ranks, &mut indices, 0, &mut buffer, &mut best);
+    best.heap.into_sorted_vec()
+}
+
+// Finds the closest pairs among indices, sorted by x and starting at rank offset, and leaves them sorted by y.
+fn search(
+    points: &[Point],
+    ranks: &[usize],
+    indices: &mut [usize],
+    offset: usize,
+    buffer: &mut Vec<usize>,
+    best: &mut Best,
+) {
+    let by_y = |a: &usize, b: &usize| points[*a].y.total_cmp(&points[*b].y);
+
+    // Brute force for 3 points or fewer.
+    if indices.len() <= 3 {
+        for i in 0..indices.len() {
+            for j in i + 1..indices.len() {
+                best.offer(Pair::new(points, indices[i], indices[j]));
+            }
+        }
+        indices.sort_unstable_by(by_y);
+        return;
+    }
+
+    let middle = indices.len() / 2;
+    let middle_x = points[indices[middle]].x;
+    {
+        let (left, right) = indices.split_at_mut(middle);
+        search(points, ranks, left, offset, buffer, best);
+        search(points, ranks, right, offset + middle, buffer, best);
+    }
+
+    // Merge both halves by y.
+    buffer.clear();
+    let (mut i, mut j) = (0, middle);
+    while i < middle || j < indices.len() {
+        if j == indices.len() || (i < middle && by_y(&indices[i], &indices[j]) != Ordering::Greater) {
+            buffer.push(indices[i]);
+            i += 1;
+        } else {
+            buffer.push(indices[j]);
+            j += 1;
+        }
+    }
+    indices.copy_from_slice(buffer);
+
+    // Points close enough to the dividing line, by y.
+    buffer.clear();
+    let bound = best.bound();
+    buffer.extend(
+        indices
+            .iter()
+            .filter(|&&index| (points[index].x - middle_x).powi(2) < bound),
+    );
+
+    // Pairs across the line, until the y distance alone is too far.
+    let split = offset + middle;
+    for a in 0..buffer.len() {
+        for b in a + 1..buffer.len() {
+            let (first, second) = (buffer[a], buffer[b]);
+            if (points[second].y - points[first].y).powi(2) >= best.bound() {
+                break;
+            }
+            if (ranks[first] < split) != (ranks[second] < split) {
+                best.offer(Pair::new(points, first, second));
+            }
+        }
+    }
+}
+
+// Returns the index of the nearest other point to every point, None if there is no other point.
+pub fn all_nearest_neighbours(points: &[Point]) -> Vec<Option<usize>> {
+    let mut tree = (0..points.len()).collect::<Vec<usize>>();
+    let mut split_x = vec![false; points.len()];
+    build(points, &mut tree, &mut split_x);
+
+    (0..points.len())
+        .map(|query| {
+            let mut nearest = None;
+            search_nearest(points, &tree, &split_x, query, &mut nearest);
+            nearest.map(|(_, index)| index)
+        })
+        .collect()
+}
+
+// Lays out a k-d tree: the median of every range is its root, split on x if split_x is set, or else y.
+fn build(points: &[Point], tree: &mut [usize], split_x: &mut [bool]) {
+    if tree.is_empty() {
+        return;
+    }
+
+    let spread = |coordinate: &dyn Fn(&Point) -> f64| {
+        let values = tree.iter().map(|&index| coordinate(&points[index]));
+        values.clone().fold(f64::MIN, f64::max) - values.fold(f64::MAX, f64::min)
+    };
+    let on_x = spread(&|point| point.x) >= spread(&|point| point.y);
+
+    let middle = tree.len() / 2;
+    let axis = |index: usize| if on_x { points[index].x } else { points[index].y };
+    tree.select_nth_unstable_by(middle, |&a, &b| axis(a).total_cmp(&axis(b)));
+    split_x[middle] = on_x;
+
+    let (left_tree, right_tree) = tree.split_at_mut(middle);
+    let (left_split, right_split) = split_x.split_at_mut(middle);
+    build(points, left_tree, left_split);
+    build(points, &mut right_tree[1..], &mut right_split[1..]);
+}
+
+// Updates nearest with the closest point to query in the tree, as (squared distance, index).
+fn search_nearest(
+    points: &[Point],
+    tree: &[usize],
+    split_x: &[bool],
+    query: usize,
+    nearest: &mut Option<(f64, usize)>,
+) {
+    if tree.is_empty() {
+        return;
+    }
+
+    let middle = tree.len() / 2;
+    let node = tree[middle];
+    if node != query {
+        let squared_distance = points[node].squared_euclidean_distance(&points[query]);
+        if nearest.is_none_or(|(best, _)| squared_distance < best) {
+            *nearest = Some((squared_distance, node));
+        }
+    }
+
+    // Side of the query first, the other one only if it can hold something closer.
+    let difference = match split_x[middle] {
+        true => points[query].x - points[node].x,
+        false => points[query].y - points[node].y,
+    };
+    let (left, right) = (
+        (&tree[..middle], &split_x[..middle]),
+        (&tree[middle + 1..], &split_x[middle + 1..]),
+    );
+    let (near, far) = if difference < 0.0 { (left, right) } else { (right, left) };
+
+    search_nearest(points, near.0, near.1, query, nearest);
+    if nearest.is_none_or(|(best, _)| difference * difference < best) {
+        search_nearest(points, far.0, far.1, query, nearest);
+    }
+}
+
+// Every pair, closest first, in O(n^2).
+fn brute_force(points: &[Point]) -> Vec<Pair> {
+    let mut pairs = Vec::new();
+    for i in 0..points.len() {
+        for j in i + 1..points.len() {
+            pairs.push(Pair::new(points, i, j));
+        }
+    }
+    pairs.sort_unstable();
+    pairs
+}
+
+// Point anywhere in the 1000 x 1000 square.
+fn random_point(rng: &mut Rng) -> Point {
+    let mut coordinate = || rng.next_u64() as f64 / u64::MAX as f64 * 1000.0;
+    Point::new(coordinate(), coordinate())
+}
+
+// Checks every search against brute force on random points, with many duplicates.
+fn cross_check(rng: &mut Rng) {
+    for round in 0..2000 {
+        let length = round % 60;
+        let range = 1 + round % 25;
+        let points = (0..length)
+            .map(|_| Point::new(rng.gen_range(0..range) as f64, rng.gen_range(0..range) as f64 * 0.5))
+            .collect::<Vec<Point>>();
+        let pairs = brute_force(&points);
+
+        for &k in [1, 2, 5, 40, pairs.len() + 3].iter() {
+            let found = k_closest_pairs(&points, k);
+            assert_eq!(found.len(), k.min(pairs.len()));
+            for (pair, expected) in found.iter().zip(pairs.iter()) {
+                assert_eq!(
+                    pair.squared_distance, expected.squared_distance,
+                    "Wrong pair in {:?}",
+                    points
+                );
+                assert!(pair.first < pair.second);
+                assert_eq!(*pair, Pair::new(&points, pair.first, pair.second));
+            }
+            let mut unique = found
+                .iter()
+                .map(|pair| (pair.first, pair.second))
+                .collect::<Vec<(usize, usize)>>();
+            unique.sort_unstable();
+            unique.dedup();
+            assert_eq!(unique.len(), found.len(), "Pair found twice in {:?}", points);
+        }
+
+        let neighbours = all_nearest_neighbours(&points);
+        for (index, neighbour) in neighbours.into_iter().enumerate() {
+            let nearest = pairs.iter().find(|pair| pair.first == index || pair.second == index);
+            match (neighbour, nearest) {
+                (Some(neighbour), Some(nearest)) => {
+                    assert_ne!(neighbour, index);
+                    let squared_distance = points[index].squared_euclidean_distance(&points[neighbour]);
+                    assert_eq!(
+                        squared_distance, nearest.squared_distance,
+                        "Wrong neighbour in {:?}",
+                        points
+                    );
+                }
+                (None, None) => {}
+                _ => panic!("Nearest neighbour of a point alone"),
+            }
+        }
+    }
+
+    println!("Cross-checked against brute force on 2000 random point sets");
+}
+
+fn main() {
+    // Ensure proper usage.
+    let mut args = env::args().skip(1).collect::<Vec<String>>();
+    let mut rng = match Rng::from_args(&mut args) {
+        Some(rng) if args.is_empty() => rng,
+        _ => {
+            eprintln!("usage: ./closest_pair [--seed <seed>]");
+            process::exit(1);
+        }
+    };
+    println!("Seed = {}", rng.seed());
+
+    let points = (0..100).map(|_| random_point(&mut rng)).collect::<Vec<Point>>();
+    let pair = closest_pair(&points).unwrap();
+    assert_eq!(pair, brute_force(&points)[0]);
+    println!(
+        "Closest pair of 100 points: {:?} and {:?}, distance {}",
+        points[pair.first],
+        points[pair.second],
+        pair.distance()
+    );
+
+    cross_check(&mut rng);
+
+    // Large inputs: random, all on one vertical line, and all the same point.
+    let length = 1_000_000;
+    let inputs = [
+        (
+            "random",
+            (0..length).map(|_| random_point(&mut rng)).collect::<Vec<Point>>(),
+        ),
+        (
+            "vertical line",
+            (0..length).map(|i| Point::new(1.0, i as f64)).collect(),
+        ),
+        ("duplicates", vec![Point::new(3.0, 4.0); length]),
+    ];
+    for (name, points) in inputs.iter() {
+        let start = Instant::now();
+        let pairs = k_closest_pairs(points, 10);
+        let pairs_time = start.elapsed();
+
+        let start = Instant::now();
+        let neighbours = all_nearest_neighbours(points);
+        let neighbours_time = start.elapsed();
+        assert!(neighbours.iter().all(|neighbour| neighbour.is_some()));
+
+        println!(
+            "\n{} points, {}: closest distance {}, 10th closest {}",
+            length,
+            name,
+            pairs[0].distance(),
+            pairs[9].distance()
+        );
+        println!(
+            "10 closest pairs in {:?}, all nearest neighbours in {:?}",
+            pairs_time, neighbours_time
+        );
+    }
+}
@@ -0,0 +1,356 @@
+/**
+ * Counting inversions.
+ * Inversion occurs when i < j, and A[i] > A[j].
+ *
+ * Piggybacks on merge sort: whenever an element of the right half is merged before the remaining elements of the
+ * left half, it forms an inversion with each of them. Counting takes O(n log n) time, and listing every inversion
+ * takes O(n log n + k) time for k inversions.
+ */
+use std::collections::HashMap;
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::hash::Hash;
+use std::process;
+
+#[allow(dead_code)]
+#[path = "../Week 3/rng.rs"]
+mod rng;
+use rng::Rng;
+
+// Returns the number of inversions in the array.
+pub fn count_inversions<T: Ord>(array: &[T]) -> u64 {
+    // Sort references to the elements, so the array itself is left untouched.
+    let mut sorted = array.iter().collect::<Vec<&T>>();
+
+    // Additional space needed to help merge sorted halves.
+    let mut temp = sorted.clone();
+    sort_and_count(&mut sorted, &mut temp)
+}
+
+// Recursively sort both halves, and count inversions within and across them.
+fn sort_and_count<T: Ord + Copy>(array: &mut [T], temp: &mut [T]) -> u64 {
+    if array.len() <= 1 {
+        return 0;
+    }
+
+    // Count left and right inversions.
+    let middle = array.len() / 2;
+    let left_inversions = sort_and_count(&mut array[..middle], &mut temp[..middle]);
+    let right_inversions = sort_and_count(&mut array[middle..], &mut temp[middle..]);
+
+    // Count split inversions.
+    let split_inversions = count_split_inversions(array, middle, temp);
+
+    left_inversions + right_inversions + split_inversions
+}
+
+// Merges the sorted halves array[..middle] and array[middle..], and returns the number of inversions across them.
+fn count_split_inversions<T: Ord + Copy>(array: &mut [T], middle: usize, temp: &mut [T]) -> u64 {
+    let (mut index1, mut index2, mut temp_index) = (0, middle, 0);
+    let mut inversion_count = 0;
+
+    // Copy smallest elements first from both sorted halves.
+    while index1 < middle && index2 < array.len() {
+        if array[index2] < array[index1] {
+            // Every remaining element of the left half is larger than this one.
+            inversion_count += (middle - index1) as u64;
+            temp[temp_index] = array[index2];
+            index2 += 1;
+        } else {
+            temp[temp_index] = array[index1];
+            index1 += 1;
+        }
+        temp_index += 1;
+    }
+
+    // Copy remaining elements.
+    temp[temp_index..temp_index + middle - index1].copy_from_slice(&array[index1..middle]);
+    temp_index += middle - index1;
+    temp[temp_index..].copy_from_slice(&array[index2..]);
+
+    // Temp array has the merged sorted values, copy them back into original array.
+    array.copy_from_slice(temp);
+    inversion_count
+}
+
+// Iterator over every inversion (i, j) of an array.
+//
+// Runs a bottom up merge sort on the indices of the array one step at a time, so it only needs O(n) memory no matter
+// how many inversions there are. Pairs come out in merge order, not sorted.
+pub struct Inversions<'a, T> {
+    array: &'a [T],
+    // Indices of the array, sorted by value within every run of [width] indices.
+    order: Vec<usize>,
+    // Runs merged so far in this pass.
+    merged: Vec<usize>,
+    // Length of the sorted runs in this pass.
+    width: usize,
+    // Positions in order of the two runs being merged.
+    left: usize,
+    left_end: usize,
+    right: usize,
+    right_end: usize,
+    // Inversions left to report for the last merged right element, (next left position, right index).
+    pending: Option<(usize, usize)>,
+}
+
+// Returns an iterator over every inversion (i, j) of the array.
+pub fn inversions<T: Ord>(array: &[T]) -> Inversions<'_, T> {
+    let mut inversions = Inversions {
+        array,
+        order: (0..array.len()).collect(),
+        merged: Vec::with_capacity(array.len()),
+        width: 1,
+        left: 0,
+        left_end: 0,
+        right: 0,
+        right_end: 0,
+        pending: None,
+    };
+    inversions.start_merge(0);
+    inversions
+}
+
+impl<'a, T: Ord> Inversions<'a, T> {
+    // Sets up the merge of the two runs starting at [start].
+    fn start_merge(&mut self, start: usize) {
+        let length = self.order.len();
+        self.left = start;
+        self.left_end = (start + self.width).min(length);
+        self.right = self.left_end;
+        self.right_end = (start + 2 * self.width).min(length);
+    }
+}
+
+impl<'a, T: Ord> Iterator for Inversions<'a, T> {
+    type Item = (usize, usize);
+
+    fn next(&mut self) -> Option<(usize, usize)> {
+        loop {
+            // Report the inversions of the last merged right element, one per call.
+            if let Some((position, j)) = self.pending {
+                if position < self.left_end {
+                    self.pending = Some((position + 1, j));
+                    return Some((self.order[position], j));
+                }
+                self.pending = None;
+            }
+
+            // Sorting is done.
+            if self.width >= self.order.len() {
+                return None;
+            }
+
+            if self.left < self.left_end && self.right < self.right_end {
+                // Merge one more element. Left indices are always smaller than right indices.
+                let (i, j) = (self.order[self.left], self.order[self.right]);
+                if self.array[j] < self.array[i] {
+                    // Every remaining element of the left run is larger than this one.
+                    self.merged.push(j);
+                    self.pending = Some((self.left, j));
+                    self.right += 1;
+                } else {
+                    self.merged.push(i);
+                    self.left += 1;
+                }
+                continue;
+            }
+
+            // One run is exhausted, the rest of the other one can't form any more inversions.
+            self.merged.extend_from_slice(&self.order[self.left..self.left_end]);
+            self.merged.extend_from_slice(&self.order[self.right..self.right_end]);
+
+            if self.right_end < self.order.len() {
+                // Move on to the next pair of runs.
+                self.start_merge(self.right_end);
+            } else {
+                // Pass is over, runs are twice as long now.
+                std::mem::swap(&mut self.order, &mut self.merged);
+                self.merged.clear();
+                self.width *= 2;
+                self.start_merge(0);
+            }
+        }
+    }
+}
+
+// Returns the number of pairs that the two rankings order differently, or None if they don't rank the same items.
+pub fn kendall_tau_distance<T: Eq + Hash>(first: &[T], second: &[T]) -> Option<u64> {
+    if first.len() != second.len() {
+        return None;
+    }
+
+    // Position of every item in the second ranking.
+    let mut positions = HashMap::with_capacity(second.len());
+    for (position, item) in second.iter().enumerate() {
+        // Rankings can't contain an item twice.
+        if positions.insert(item, position).is_some() {
+            return None;
+        }
+    }
+
+    // Pairs ordered differently are the inversions of the first ranking written as second ranking positions.
+    let mut ranks = Vec::with_capacity(first.len());
+    let mut ranked = vec![false; first.len()];
+    for item in first {
+        let position = *positions.get(item)?;
+        // Item repeated in the first ranking, so another one of the second is missing from it.
+        if std::mem::replace(&mut ranked[position], true) {
+            return None;
+        }
+        ranks.push(position);
+    }
+
+    Some(count_inversions(&ranks))
+}
+
+// Kendall tau distance divided by the number of pairs, 0 for identical rankings and 1 for reversed ones.
+pub fn normalized_kendall_tau_distance<T: Eq + Hash>(first: &[T], second: &[T]) -> Option<f64> {
+    let distance = kendall_tau_distance(first, second)?;
+    let pairs = first.len() as u64 * (first.len() as u64).saturating_sub(1) / 2;
+
+    match pairs {
+        0 => Some(0.0),
+        pairs => Some(distance as f64 / pairs as f64),
+    }
+}
+
+// Checks counting, listing and Kendall tau distance against brute force on small random arrays.
+fn check(rng: &mut Rng) {
+    for round in 0..2000 {
+        // Few distinct values, so there are ties too.
+        let length = round % 40;
+        let array = (0..length).map(|_| rng.gen_range(0..10)).collect::<Vec<usize>>();
+        let expected = (0..length)
+            .flat_map(|i| (i + 1..length).map(move |j| (i, j)))
+            .filter(|&(i, j)| array[i] > array[j])
+            .collect::<Vec<(usize, usize)>>();
+
+        assert_eq!(
+            count_inversions(&array),
+            expected.len() as u64,
+            "Wrong count for {:?}",
+            array
+        );
+        let mut listed = inversions(&array).collect::<Vec<(usize, usize)>>();
+        listed.sort_unstable();
+        assert_eq!(listed, expected, "Wrong inversions of {:?}", array);
+
+        // Two random rankings of the same items, and the pairs they order differently.
+        let mut first = (0..length).collect::<Vec<usize>>();
+        rng.shuffle(&mut first);
+        let mut second = first.clone();
+        rng.shuffle(&mut second);
+        let mut positions = vec![0; length];
+        for (position, &item) in second.iter().enumerate() {
+            positions[item] = position;
+        }
+        let discordant = (0..length)
+            .flat_map(|i| (i + 1..length).map(move |j| (i, j)))
+            .filter(|&(i, j)| positions[first[i]] > positions[first[j]])
+            .count() as u64;
+
+        assert_eq!(kendall_tau_distance(&first, &second), Some(discordant));
+        if length >= 2 {
+            let pairs = (length * (length - 1) / 2) as f64;
+            assert_eq!(
+                normalized_kendall_tau_distance(&first, &second),
+                Some(discordant as f64 / pairs)
+            );
+        }
+    }
+
+    // Rankings of different lengths, with a repeated item, or of different items.
+    assert_eq!(kendall_tau_distance(&[1, 2], &[1]), None);
+    assert_eq!(kendall_tau_distance(&[1, 1], &[1, 2]), None);
+    assert_eq!(kendall_tau_distance(&[1, 2], &[1, 1]), None);
+    assert_eq!(kendall_tau_distance(&[1, 3], &[1, 2]), None);
+    assert_eq!(normalized_kendall_tau_distance(&[1, 2, 3], &[3, 2, 1]), Some(1.0));
+
+    println!("Checked counts, inversions and Kendall tau distances of 2000 random arrays against brute force");
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let usage = "usage: ./inversion_count [filename] [--list <count>] [--kendall <other ranking file>] [--seed <seed>]";
+
+    // Parse arguments.
+    let mut args = env::args().skip(1).collect::<Vec<String>>();
+    let mut rng = Rng::from_args(&mut args).unwrap_or_else(|| {
+        eprintln!("{}", usage);
+        process::exit(1);
+    });
+    println!("Seed = {}", rng.seed());
+    check(&mut rng);
+
+    let mut filename = String::from("integer_array.txt");
+    let mut list_count = 0;
+    let mut other_ranking = None;
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--list" => match args.next().and_then(|count| count.parse::<usize>().ok()) {
+                Some(count) => list_count = count,
+                None => {
+                    eprintln!("{}", usage);
+                    process::exit(1);
+                }
+            },
+            "--kendall" => match args.next() {
+                Some(other) => other_ranking = Some(other),
+                None => {
+                    eprintln!("{}", usage);
+                    process::exit(1);
+                }
+            },
+            _ => filename = arg,
+        }
+    }
+
+    // Load one integer per line.
+    let contents = fs::read_to_string(&filename)?;
+    let mut array = Vec::new();
+    for (index, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if !line.is_empty() {
+            array
+                .push(line.parse::<i64>().map_err(|error| {
+                    format!("{}:{}: '{}' is not an integer ({})", filename, index + 1, line, error)
+                })?);
+        }
+    }
+
+    println!("Inversions = {}", count_inversions(&array));
+
+    // List the first few inversions.
+    for (i, j) in inversions(&array).take(list_count) {
+        println!("({}, {}): {} > {}", i, j, array[i], array[j]);
+    }
+
+    // Compare with another ranking of the same items.
+    if let Some(other_filename) = other_ranking {
+        let other_contents = fs::read_to_string(&other_filename)?;
+        let first = contents
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<&str>>();
+        let second = other_contents
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<&str>>();
+
+        match (
+            kendall_tau_distance(&first, &second),
+            normalized_kendall_tau_distance(&first, &second),
+        ) {
+            (Some(distance), Some(normalized)) => {
+                println!("Kendall tau distance = {} (normalized {:.6})", distance, normalized)
+            }
+            _ => println!("{} and {} don't rank the same items", filename, other_filename),
+        }
+    }
+
+    Ok(())
+}
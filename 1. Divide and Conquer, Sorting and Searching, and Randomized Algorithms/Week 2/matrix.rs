@@ -0,0 +1,384 @@
+/**
+ * Dense matrices over numeric types, with three ways to multiply them.
+ *
+ * - Naive: O(n^3), row by row in i-k-j order so the inner loop walks both matrices in memory order.
+ * - Blocked: the same products, tile by tile, so the tiles in use stay in cache.
+ * - Strassen: split both matrices into quadrants and combine 7 products of them instead of 8, for O(n^2.81).
+ *   Odd dimensions get one padding row or column of zeros at each level, and products with a dimension at or below
+ *   the cutoff fall back to naive multiplication, which is faster on small matrices.
+ *
+ * Integer arithmetic wraps, so Strassen's subtractions also work on unsigned types and every method agrees exactly.
+ * Floating point results of Strassen differ from naive ones by rounding only.
+ */
+use std::fmt::Debug;
+use std::ops::{Add, Index, IndexMut, Sub};
+use std::time::Instant;
+use std::{env, process};
+
+#[allow(dead_code)]
+#[path = "../Week 3/rng.rs"]
+mod rng;
+use rng::Rng;
+
+// Dimension at or below which Strassen multiplies naively.
+pub const STRASSEN_CUTOFF: usize = 64;
+// Rows and columns of the tiles in blocked multiplication.
+pub const BLOCK_SIZE: usize = 64;
+
+// Numbers a matrix can hold.
+pub trait Scalar: Copy + PartialEq + Debug {
+    fn zero() -> Self;
+    fn add(self, other: Self) -> Self;
+    fn sub(self, other: Self) -> Self;
+    fn mul(self, other: Self) -> Self;
+}
+
+macro_rules! integer_scalar {
+    ($($type:ty),*) => {$(
+        impl Scalar for $type {
+            fn zero() -> $type {
+                0
+            }
+            fn add(self, other: $type) -> $type {
+                self.wrapping_add(other)
+            }
+            fn sub(self, other: $type) -> $type {
+                self.wrapping_sub(other)
+            }
+            fn mul(self, other: $type) -> $type {
+                self.wrapping_mul(other)
+            }
+        }
+    )*};
+}
+
+macro_rules! float_scalar {
+    ($($type:ty),*) => {$(
+        impl Scalar for $type {
+            fn zero() -> $type {
+                0.0
+            }
+            fn add(self, other: $type) -> $type {
+                self + other
+            }
+            fn sub(self, other: $type) -> $type {
+                self - other
+            }
+            fn mul(self, other: $type) -> $type {
+                self * other
+            }
+        }
+    )*};
+}
+
+integer_scalar!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+float_scalar!(f32, f64);
+
+// Matrix of rows x columns values, stored row after row.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Matrix<T> {
+    rows: usize,
+    columns: usize,
+    data: Vec<T>,
+}
+
+impl<T: Scalar> Matrix<T> {
+    // Matrix of zeros.
+    pub fn new(rows: usize, columns: usize) -> Matrix<T> {
+        Matrix {
+            rows,
+            columns,
+            data: vec![T::zero(); rows * columns],
+        }
+    }
+
+    // Matrix with value(row, column) in every cell.
+    pub fn from_fn<F: FnMut(usize, usize) -> T>(rows: usize, columns: usize, mut value: F) -> Matrix<T> {
+        let mut data = Vec::with_capacity(rows * columns);
+        for row in 0..rows {
+            for column in 0..columns {
+                data.push(value(row, column));
+            }
+        }
+        Matrix { rows, columns, data }
+    }
+
+    // Matrix from its rows, or None if they are not all the same length.
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Option<Matrix<T>> {
+        let columns = rows.first().map_or(0, |row| row.len());
+        if rows.iter().any(|row| row.len() != columns) {
+            return None;
+        }
+
+        Some(Matrix {
+            rows: rows.len(),
+            columns,
+            data: rows.into_iter().flatten().collect(),
+        })
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn columns(&self) -> usize {
+        self.columns
+    }
+
+    // Values of one row.
+    pub fn row(&self, row: usize) -> &[T] {
+        &self.data[row * self.columns..(row + 1) * self.columns]
+    }
+
+    // Panics unless self can be multiplied by other.
+    fn check_product(&self, other: &Matrix<T>) {
+        assert_eq!(
+            self.columns, other.rows,
+            "Can't multiply a {} x {} matrix by a {} x {} matrix",
+            self.rows, self.columns, other.rows, other.columns
+        );
+    }
+
+    // Product, in the textbook O(n^3) way.
+    pub fn multiply_naive(&self, other: &Matrix<T>) -> Matrix<T> {
+        self.check_product(other);
+        let mut product = Matrix::new(self.rows, other.columns);
+        for i in 0..self.rows {
+            let product_row: &mut [T] = &mut product.data[i * other.columns..(i + 1) * other.columns];
+            for (k, &value) in self.row(i).iter().enumerate() {
+                for (cell, &other_value) in product_row.iter_mut().zip(other.row(k)) {
+                    *cell = cell.add(value.mul(other_value));
+                }
+            }
+        }
+        product
+    }
+
+    // Product, one block_size x block_size tile at a time.
+    pub fn multiply_blocked(&self, other: &Matrix<T>, block_size: usize) -> Matrix<T> {
+        self.check_product(other);
+        let block_size = block_size.max(1);
+        let mut product = Matrix::new(self.rows, other.columns);
+
+        for row_start in (0..self.rows).step_by(block_size) {
+            for k_start in (0..self.columns).step_by(block_size) {
+                for column_start in (0..other.columns).step_by(block_size) {
+                    let column_end = (column_start + block_size).min(other.columns);
+                    for i in row_start..(row_start + block_size).min(self.rows) {
+                        for k in k_start..(k_start + block_size).min(self.columns) {
+                            let value = self[(i, k)];
+                            let other_row = &other.row(k)[column_start..column_end];
+                            let start = i * other.columns;
+                            let product_row: &mut [T] = &mut product.data[start + column_start..start + column_end];
+                            for (cell, &other_value) in product_row.iter_mut().zip(other_row) {
+                                *cell = cell.add(value.mul(other_value));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        product
+    }
+
+    // Product with Strassen's 7 multiplications, naive once a dimension is at most cutoff.
+    pub fn multiply_strassen(&self, other: &Matrix<T>, cutoff: usize) -> Matrix<T> {
+        self.check_product(other);
+        let (m, k, n) = (self.rows, self.columns, other.columns);
+        if m.min(k).min(n) <= cutoff.max(1) {
+            return self.multiply_naive(other);
+        }
+
+        // Quadrants, padded with zeros to even dimensions.
+        let (half_m, half_k, half_n) = (m.div_ceil(2), k.div_ceil(2), n.div_ceil(2));
+        let a11 = self.block(0, 0, half_m, half_k);
+        let a12 = self.block(0, half_k, half_m, half_k);
+        let a21 = self.block(half_m, 0, half_m, half_k);
+        let a22 = self.block(half_m, half_k, half_m, half_k);
+        let b11 = other.block(0, 0, half_k, half_n);
+        let b12 = other.block(0, half_n, half_k, half_n);
+        let b21 = other.block(half_k, 0, half_k, half_n);
+        let b22 = other.block(half_k, half_n, half_k, half_n);
+
+        let m1 = (&a11 + &a22).multiply_strassen(&(&b11 + &b22), cutoff);
+        let m2 = (&a21 + &a22).multiply_strassen(&b11, cutoff);
+        let m3 = a11.multiply_strassen(&(&b12 - &b22), cutoff);
+        let m4 = a22.multiply_strassen(&(&b21 - &b11), cutoff);
+        let m5 = (&a11 + &a12).multiply_strassen(&b22, cutoff);
+        let m6 = (&a21 - &a11).multiply_strassen(&(&b11 + &b12), cutoff);
+        let m7 = (&a12 - &a22).multiply_strassen(&(&b21 + &b22), cutoff);
+
+        let mut product = Matrix::new(m, n);
+        product.set_block(0, 0, &(&(&(&m1 + &m4) - &m5) + &m7));
+        product.set_block(0, half_n, &(&m3 + &m5));
+        product.set_block(half_m, 0, &(&m2 + &m4));
+        product.set_block(half_m, half_n, &(&(&(&m1 - &m2) + &m3) + &m6));
+        product
+    }
+
+    // Copy of the rows x columns block starting at (row, column), zeros past the edges.
+    fn block(&self, row: usize, column: usize, rows: usize, columns: usize) -> Matrix<T> {
+        let mut block = Matrix::new(rows, columns);
+        for i in 0..rows.min(self.rows.saturating_sub(row)) {
+            let width = columns.min(self.columns.saturating_sub(column));
+            let source = &self.row(row + i)[column..column + width];
+            block.data[i * columns..i * columns + width].copy_from_slice(source);
+        }
+        block
+    }
+
+    // Writes the block starting at (row, column), dropping whatever falls past the edges.
+    fn set_block(&mut self, row: usize, column: usize, block: &Matrix<T>) {
+        for i in 0..block.rows.min(self.rows.saturating_sub(row)) {
+            let width = block.columns.min(self.columns.saturating_sub(column));
+            let start = (row + i) * self.columns + column;
+            self.data[start..start + width].copy_from_slice(&block.row(i)[..width]);
+        }
+    }
+
+    // Applies f to the cells of two matrices of the same shape.
+    fn zip_with<F: Fn(T, T) -> T>(&self, other: &Matrix<T>, f: F) -> Matrix<T> {
+        assert!(
+            self.rows == other.rows && self.columns == other.columns,
+            "Matrices of different shapes"
+        );
+        Matrix {
+            rows: self.rows,
+            columns: self.columns,
+            data: self
+                .data
+                .iter()
+                .zip(other.data.iter())
+                .map(|(&a, &b)| f(a, b))
+                .collect(),
+        }
+    }
+}
+
+impl<T> Index<(usize, usize)> for Matrix<T> {
+    type Output = T;
+
+    fn index(&self, (row, column): (usize, usize)) -> &T {
+        assert!(column < self.columns, "Column {} out of {}", column, self.columns);
+        &self.data[row * self.columns + column]
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Matrix<T> {
+    fn index_mut(&mut self, (row, column): (usize, usize)) -> &mut T {
+        assert!(column < self.columns, "Column {} out of {}", column, self.columns);
+        &mut self.data[row * self.columns + column]
+    }
+}
+
+impl<T: Scalar> Add for &Matrix<T> {
+    type Output = Matrix<T>;
+
+    fn add(self, other: &Matrix<T>) -> Matrix<T> {
+        self.zip_with(other, T::add)
+    }
+}
+
+impl<T: Scalar> Sub for &Matrix<T> {
+    type Output = Matrix<T>;
+
+    fn sub(self, other: &Matrix<T>) -> Matrix<T> {
+        self.zip_with(other, T::sub)
+    }
+}
+
+// Checks blocked and Strassen products against naive ones on random matrices of many shapes.
+fn cross_check(rng: &mut Rng) {
+    for round in 0..500 {
+        let (m, k, n) = (
+            1 + rng.gen_range(0..40),
+            1 + rng.gen_range(0..40),
+            1 + rng.gen_range(0..40),
+        );
+        let cutoff = round % 6;
+        let block_size = 1 + round % 9;
+
+        // Signed values.
+        let a = Matrix::from_fn(m, k, |_, _| rng.gen_range(0..201) as i64 - 100);
+        let b = Matrix::from_fn(k, n, |_, _| rng.gen_range(0..201) as i64 - 100);
+        let expected = a.multiply_naive(&b);
+        assert_eq!(
+            a.multiply_blocked(&b, block_size),
+            expected,
+            "Blocked failed on {} x {} x {}",
+            m,
+            k,
+            n
+        );
+        assert_eq!(
+            a.multiply_strassen(&b, cutoff),
+            expected,
+            "Strassen failed on {} x {} x {}",
+            m,
+            k,
+            n
+        );
+
+        // Unsigned values, where Strassen's differences wrap around.
+        let a = Matrix::from_fn(m, k, |_, _| rng.next_u64() as u32);
+        let b = Matrix::from_fn(k, n, |_, _| rng.next_u64() as u32);
+        assert_eq!(a.multiply_strassen(&b, cutoff), a.multiply_naive(&b));
+
+        // Floats, equal up to rounding.
+        let a = Matrix::from_fn(m, k, |_, _| rng.gen_range(0..1000) as f64 / 7.0);
+        let b = Matrix::from_fn(k, n, |_, _| rng.gen_range(0..1000) as f64 / 3.0);
+        let (expected, product) = (a.multiply_naive(&b), a.multiply_strassen(&b, cutoff));
+        for (x, y) in expected.data.iter().zip(product.data.iter()) {
+            assert!(
+                (x - y).abs() <= 1e-9 * x.abs().max(1.0),
+                "Strassen is off: {} against {}",
+                y,
+                x
+            );
+        }
+    }
+
+    let rows = Matrix::from_rows(vec![vec![1, 2], vec![3, 4], vec![5, 6]]).unwrap();
+    let columns = Matrix::from_rows(vec![vec![1, 0, -1], vec![2, 1, 0]]).unwrap();
+    let expected = Matrix::from_rows(vec![vec![5, 2, -1], vec![11, 4, -3], vec![17, 6, -5]]).unwrap();
+    assert_eq!(rows.multiply_strassen(&columns, 0), expected);
+    assert!(Matrix::from_rows(vec![vec![1], vec![2, 3]]).is_none());
+
+    println!("Cross-checked blocked and Strassen against naive on 500 random shapes");
+}
+
+fn main() {
+    // Ensure proper usage.
+    let mut args = env::args().skip(1).collect::<Vec<String>>();
+    let mut rng = match Rng::from_args(&mut args) {
+        Some(rng) if args.is_empty() => rng,
+        _ => {
+            eprintln!("usage: ./matrix [--seed <seed>]");
+            process::exit(1);
+        }
+    };
+    println!("Seed = {}", rng.seed());
+
+    cross_check(&mut rng);
+
+    // Timings on a power of two and on an odd size, where Strassen pads at every level.
+    for &size in [512, 1000].iter() {
+        let a = Matrix::from_fn(size, size, |_, _| rng.gen_range(0..1000) as i64);
+        let b = Matrix::from_fn(size, size, |_, _| rng.gen_range(0..1000) as i64);
+        println!("\n{} x {}", size, size);
+
+        let start = Instant::now();
+        let expected = a.multiply_naive(&b);
+        println!("Naive: {:?}", start.elapsed());
+
+        let start = Instant::now();
+        assert_eq!(a.multiply_blocked(&b, BLOCK_SIZE), expected);
+        println!("Blocked ({} x {} tiles): {:?}", BLOCK_SIZE, BLOCK_SIZE, start.elapsed());
+
+        for &cutoff in [32, STRASSEN_CUTOFF, 128, 256].iter() {
+            let start = Instant::now();
+            assert_eq!(a.multiply_strassen(&b, cutoff), expected);
+            println!("Strassen (cutoff {}): {:?}", cutoff, start.elapsed());
+        }
+    }
+}
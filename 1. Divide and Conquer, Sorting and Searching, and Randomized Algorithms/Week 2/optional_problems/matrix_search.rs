@@ -0,0 +1,285 @@
+/**
+ * Searches on matrices, the two dimensional versions of question_2.rs and question_3.rs.
+ *
+ * Matrices are closures from (row, column) to a value, so they can be computed rather than stored, and every search
+ * returns the number of probes it made.
+ *
+ * - peak_2d: a local peak (no neighbour is larger) of a rows x columns grid in O(rows + columns) probes. Splits the
+ *   window in half across its longer side, and keeps the half holding the largest value seen so far or a neighbour
+ *   larger than the maximum of the dividing line. That value is never smaller than the cells around the window,
+ *   so climbing from it never leaves the window, and it holds a peak. An n x n grid takes at most about 3n probes.
+ * - sorted_search: a value in a matrix whose rows and columns are sorted, walking from the top right corner. Every
+ *   probe discards a row or a column, so at most rows + columns - 1 probes.
+ * - fixed_point: a cell where a[i][j] = i + j in a matrix of distinct integers whose rows and columns strictly
+ *   increase. a[i][j] - i - j is then sorted along rows and columns, so this is a sorted search for 0.
+ */
+use std::cmp::Ordering;
+use std::{env, process};
+
+#[allow(dead_code)]
+#[path = "../../Week 3/rng.rs"]
+mod rng;
+use rng::Rng;
+
+// Matrix read through a closure, counting the probes.
+struct Grid<F> {
+    rows: usize,
+    columns: usize,
+    value: F,
+    probes: u64,
+}
+
+impl<T, F: Fn(usize, usize) -> T> Grid<F> {
+    fn get(&mut self, row: usize, column: usize) -> T {
+        self.probes += 1;
+        (self.value)(row, column)
+    }
+}
+
+// Returns a cell none of whose 4 neighbours is larger, or None if the grid is empty, and the probes made.
+pub fn peak_2d<T, F>(rows: usize, columns: usize, value: F) -> (Option<(usize, usize)>, u64)
+where
+    T: PartialOrd,
+    F: Fn(usize, usize) -> T,
+{
+    if rows == 0 || columns == 0 {
+        return (None, 0);
+    }
+
+    let mut grid = Grid {
+        rows,
+        columns,
+        value,
+        probes: 0,
+    };
+    // Window, as half open row and column ranges.
+    let (mut top, mut bottom, mut left, mut right) = (0, rows, 0, columns);
+    // Largest value seen inside the window, at least as large as every cell around it.
+    let mut best: Option<((usize, usize), T)> = None;
+
+    loop {
+        // Cut across the longer side, along a line of the shorter one.
+        let split_columns = right - left >= bottom - top;
+        let line = match split_columns {
+            true => (top..bottom)
+                .map(|row| (row, (left + right) / 2))
+                .collect::<Vec<(usize, usize)>>(),
+            false => (left..right).map(|column| ((top + bottom) / 2, column)).collect(),
+        };
+
+        // Maximum of the dividing line.
+        let mut line_max: Option<((usize, usize), T)> = None;
+        for (row, column) in line {
+            let value = grid.get(row, column);
+            if line_max.as_ref().is_none_or(|(_, max)| value > *max) {
+                line_max = Some(((row, column), value));
+            }
+        }
+        let line_max = line_max.unwrap();
+
+        // The best value so far lies off the line, keep its half.
+        let ((row, column), value) = match best {
+            Some(((row, column), value)) if value > line_max.1 => {
+                match split_columns {
+                    true if column < (left + right) / 2 => right = (left + right) / 2,
+                    true => left = (left + right) / 2 + 1,
+                    false if row < (top + bottom) / 2 => bottom = (top + bottom) / 2,
+                    false => top = (top + bottom) / 2 + 1,
+                }
+                best = Some(((row, column), value));
+                continue;
+            }
+            _ => line_max,
+        };
+
+        // Neighbours across the line, inside the window. The ones outside are never larger than the best value.
+        let neighbours = match split_columns {
+            true => [(row, column.wrapping_sub(1)), (row, column + 1)],
+            false => [(row.wrapping_sub(1), column), (row + 1, column)],
+        };
+        let mut larger: Option<((usize, usize), T)> = None;
+        for &(neighbour_row, neighbour_column) in neighbours.iter() {
+            if (top..bottom).contains(&neighbour_row) && (left..right).contains(&neighbour_column) {
+                let neighbour_value = grid.get(neighbour_row, neighbour_column);
+                let current_max = larger.as_ref().map_or(&value, |(_, larger_value)| larger_value);
+                if neighbour_value > *current_max {
+                    larger = Some(((neighbour_row, neighbour_column), neighbour_value));
+                }
+            }
+        }
+
+        match larger {
+            // Larger than the whole line and everything around the window, so a peak is on its side.
+            Some(((neighbour_row, neighbour_column), neighbour_value)) => {
+                match split_columns {
+                    true if neighbour_column < column => right = column,
+                    true => left = column + 1,
+                    false if neighbour_row < row => bottom = row,
+                    false => top = row + 1,
+                }
+                best = Some(((neighbour_row, neighbour_column), neighbour_value));
+            }
+            None => return (Some((row, column)), grid.probes),
+        }
+    }
+}
+
+// Returns a cell holding the target in a matrix with sorted rows and columns, or None, and the probes made.
+pub fn sorted_search<T, F>(rows: usize, columns: usize, value: F, target: &T) -> (Option<(usize, usize)>, u64)
+where
+    T: Ord,
+    F: Fn(usize, usize) -> T,
+{
+    let mut grid = Grid {
+        rows,
+        columns,
+        value,
+        probes: 0,
+    };
+    if grid.rows == 0 || grid.columns == 0 {
+        return (None, 0);
+    }
+
+    // Everything left of the column is smaller, everything below the row is larger.
+    let (mut row, mut column) = (0, grid.columns - 1);
+    loop {
+        match grid.get(row, column).cmp(target) {
+            Ordering::Equal => return (Some((row, column)), grid.probes),
+            Ordering::Greater if column > 0 => column -= 1,
+            Ordering::Less if row + 1 < grid.rows => row += 1,
+            _ => return (None, grid.probes),
+        }
+    }
+}
+
+// Returns a cell where matrix[i][j] = i + j, or None, and the probes made. Rows and columns must strictly increase.
+pub fn fixed_point(matrix: &[Vec<i64>]) -> (Option<(usize, usize)>, u64) {
+    let columns = matrix.first().map_or(0, |row| row.len());
+    sorted_search(matrix.len(), columns, |i, j| matrix[i][j] - (i + j) as i64, &0)
+}
+
+// Whether no neighbour of the cell is larger.
+fn is_peak(grid: &[Vec<i32>], row: usize, column: usize) -> bool {
+    let neighbours = [
+        (row.wrapping_sub(1), column),
+        (row + 1, column),
+        (row, column.wrapping_sub(1)),
+        (row, column + 1),
+    ];
+    neighbours
+        .iter()
+        .all(|&(i, j)| match grid.get(i).and_then(|cells| cells.get(j)) {
+            Some(&value) => value <= grid[row][column],
+            None => true,
+        })
+}
+
+// Grid holding one long increasing path that snakes through every other row, so climbing uphill visits half the cells.
+fn snake(rows: usize, columns: usize) -> Vec<Vec<i32>> {
+    let mut grid = vec![vec![0; columns]; rows];
+    let mut step = 0;
+    for row in (0..rows).step_by(2) {
+        let cells = (0..columns).map(|column| if row % 4 == 0 { column } else { columns - 1 - column });
+        for column in cells {
+            step += 1;
+            grid[row][column] = step;
+        }
+        // Down to the next path row, at the end this one finished on.
+        if row + 2 < rows {
+            step += 1;
+            grid[row + 1][if row % 4 == 0 { columns - 1 } else { 0 }] = step;
+        }
+    }
+    grid
+}
+
+fn main() {
+    // Ensure proper usage.
+    let mut args = env::args().skip(1).collect::<Vec<String>>();
+    let mut rng = match Rng::from_args(&mut args) {
+        Some(rng) if args.is_empty() => rng,
+        _ => {
+            eprintln!("usage: ./matrix_search [--seed <seed>]");
+            process::exit(1);
+        }
+    };
+    println!("Seed = {}", rng.seed());
+
+    // Peaks of random grids with many ties, and snakes, of every shape up to 30 x 30.
+    let mut worst_ratio: f64 = 0.0;
+    for rows in 1..=30 {
+        for columns in 1..=30 {
+            let random = (0..rows)
+                .map(|_| (0..columns).map(|_| rng.gen_range(0..5) as i32).collect())
+                .collect::<Vec<Vec<i32>>>();
+
+            for grid in [random, snake(rows, columns)].iter() {
+                let (peak, probes) = peak_2d(rows, columns, |i, j| grid[i][j]);
+                let (row, column) = peak.unwrap();
+                assert!(
+                    is_peak(grid, row, column),
+                    "({}, {}) is not a peak of {:?}",
+                    row,
+                    column,
+                    grid
+                );
+
+                let log = (usize::BITS - (rows * columns).leading_zeros()) as u64;
+                assert!(
+                    probes <= 2 * (rows + columns) as u64 + 2 * log,
+                    "{} probes for {} x {}",
+                    probes,
+                    rows,
+                    columns
+                );
+                worst_ratio = worst_ratio.max(probes as f64 / rows.max(columns) as f64);
+            }
+        }
+    }
+    assert_eq!(peak_2d(0, 5, |_, _| 0), (None, 0));
+    println!(
+        "Peaks of grids up to 30 x 30 verified, at most {:.2}n probes",
+        worst_ratio
+    );
+
+    let n = 1000;
+    let grid = snake(n, n);
+    let (peak, probes) = peak_2d(n, n, |i, j| grid[i][j]);
+    let (row, column) = peak.unwrap();
+    assert!(is_peak(&grid, row, column));
+    println!(
+        "Snake of {} x {}: peak {} at ({}, {}) in {} probes",
+        n, n, grid[row][column], row, column, probes
+    );
+
+    // Every value of random sorted matrices, and values between them.
+    for rows in 0..=12 {
+        for columns in 0..=12 {
+            let mut matrix = vec![vec![0i64; columns]; rows];
+            for i in 0..rows {
+                for j in 0..columns {
+                    let above = if i > 0 { matrix[i - 1][j] } else { 0 };
+                    let before = if j > 0 { matrix[i][j - 1] } else { 0 };
+                    matrix[i][j] = above.max(before) + 2 * rng.gen_range(0..3) as i64;
+                }
+            }
+
+            for target in -1..=2 * (4 * (rows + columns) as i64 + 1) {
+                let (found, probes) = sorted_search(rows, columns, |i, j| matrix[i][j], &target);
+                assert!(probes as usize <= (rows + columns).saturating_sub(1));
+                let present = matrix.iter().any(|row| row.contains(&target));
+                match found {
+                    Some((i, j)) => assert_eq!(matrix[i][j], target),
+                    None => assert!(!present, "Missed {} in {:?}", target, matrix),
+                }
+            }
+        }
+    }
+    println!("Sorted matrix search verified, at most rows + columns - 1 probes");
+
+    // a[i][j] = i + j at (2, 1) only.
+    let matrix = vec![vec![-4, -1, 0, 5], vec![-2, 1, 4, 8], vec![0, 3, 6, 10]];
+    let (found, probes) = fixed_point(&matrix);
+    assert_eq!(found, Some((2, 1)));
+    println!("Fixed point of {:?} = {:?} in {} probes", matrix, found, probes);
+}
@@ -5,31 +5,9 @@
 use std::error::Error;
 use std::io;
 
-fn perform_knockout_tournament(elements: &[i32], max_element: i32) -> Vec<i32> {
-    // Save those elements that were compared with max element in knockout tournament.
-    let mut saved_elements: Vec<i32> = Vec::new();
-
-    let mut remaining_elements = elements.clone().to_vec();
-    while remaining_elements.len() > 1 {
-        let mut result = Vec::new();
-
-        // Perform knockout in pairs.
-        for i in (0..remaining_elements.len()).step_by(2) {
-            let first = remaining_elements[i];
-            let second = remaining_elements[i + 1];
-            result.push(std::cmp::max(first, second));
-
-            if first == max_element || second == max_element {
-                saved_elements.push(std::cmp::min(first, second));
-            }
-        }
-
-        // Perform knockout on remaining elements.
-        remaining_elements = result.clone().to_vec();
-    }
-
-    return saved_elements;
-}
+#[allow(dead_code)]
+mod tournament_tree;
+use tournament_tree::TournamentTree;
 
 // Helper macro to read input.
 macro_rules! read {
@@ -51,13 +29,12 @@ fn main() -> Result<(), Box<dyn Error>> {
         elements[i] = input;
     }
 
-    // Find the largest number in the input.
-    let max_element = *elements.iter().max().unwrap();
-
-    // Perform the knockout tournament, and get those values that were compared with the max element.
-    let compared_values = perform_knockout_tournament(&elements, max_element);
-
-    println!("Second largest value = {}", compared_values.iter().max().unwrap());
+    // Play the knockout tournament, the runner up only has to beat those the winner beat, one per round.
+    let tree = TournamentTree::new(elements);
+    match tree.top_k(2)[..] {
+        [_, second_largest] => println!("Second largest value = {}", second_largest),
+        _ => println!("Need at least two elements to find the second largest"),
+    }
 
     Ok(())
 }
@@ -6,6 +6,10 @@
 use std::error::Error;
 use std::io;
 
+#[allow(dead_code)]
+mod search;
+use search::peak;
+
 // Helper function to read input.
 macro_rules! read {
     ($out:ident as $type:ty) => {
@@ -16,24 +20,9 @@ macro_rules! read {
 }
 
 fn find_max_element(elements: &[i32]) -> Option<i32> {
-    let mut start_index: i32 = 0;
-    let mut end_index: i32 = elements.len() as i32 - 1;
-
-    // Do a binary search on input.
-    while start_index <= end_index {
-        let middle_index = ((start_index + end_index) / 2) as i32;
-
-        if middle_index - 1 >= start_index && elements[middle_index as usize] < elements[middle_index as usize - 1] {
-            end_index = middle_index - 1;
-        } else if middle_index + 1 <= end_index && elements[middle_index as usize] < elements[middle_index as usize + 1] {
-            start_index = middle_index + 1;
-        } else {
-            return Some(elements[middle_index as usize]);
-        }
-    }
-
-    // Array is not a unimodel array.
-    return None;
+    // Binary search for the first element that is not smaller than the next one.
+    let index = peak(0..elements.len() as i64, |i| elements[i as usize])?;
+    Some(elements[index as usize])
 }
 
 fn main() -> Result<(), Box<dyn Error>> {
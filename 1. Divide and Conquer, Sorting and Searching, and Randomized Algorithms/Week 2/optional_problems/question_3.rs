@@ -6,6 +6,10 @@
 use std::error::Error;
 use std::io;
 
+#[allow(dead_code)]
+mod search;
+use search::partition_point;
+
 // Helper macro to read input.
 macro_rules! read {
     ($out:ident as $type:ty) => {
@@ -17,23 +21,12 @@ macro_rules! read {
 
 // Returns that index where a[i] = i, None otherwise.
 fn find_result(elements: &[i32]) -> Option<usize> {
-    let mut start_index = 0_i32;
-    let mut end_index = elements.len() as i32 - 1;
-
-    // Perform binary search on array.
-    while start_index <= end_index {
-        let middle_index: i32 = (start_index + end_index) / 2 as i32;
-
-        if elements[middle_index as usize] < middle_index {
-            start_index = middle_index + 1;
-        } else if elements[middle_index as usize] > middle_index {
-            end_index = middle_index - 1;
-        } else {
-            return Some(middle_index as usize);
-        }
+    // Elements are distinct integers, so a[i] - i never decreases. Find the first i where it is not negative.
+    let index = partition_point(0..elements.len() as i64, |i| (elements[i as usize] as i64) < i) as usize;
+    match elements.get(index) {
+        Some(&value) if value as usize == index => Some(index),
+        _ => None,
     }
-
-    return None;
 }
 
 fn main() -> Result<(), Box<dyn Error>> {
@@ -0,0 +1,260 @@
+/**
+ * Binary search toolkit over closures, for values that are computed rather than stored in an array.
+ *
+ * - partition_point: first x where a predicate that is true and then false turns false, like slice::partition_point
+ *   but over a range of integers.
+ * - peak: maximum of a unimodal function (strictly increasing, then strictly decreasing), by binary search on the
+ *   sign of f(x + 1) - f(x). 2⌈log n⌉ evaluations.
+ * - ternary_search: same maximum, dropping a third of the range for every 2 evaluations.
+ * - golden_section_search: same maximum, probing at Fibonacci ratios so that every step reuses one of the previous
+ *   probes. About 1.44 log n evaluations, the fewest of the three, for functions that are expensive to compute.
+ * - bitonic_search: index of an element in an array that increases and then decreases, in O(log n).
+ *
+ * Every search finds a maximum. Wrap values with std::cmp::Reverse to minimize a cost instead.
+ */
+use std::cmp::Ordering;
+use std::ops::Range;
+
+// Returns the first x in range where the predicate is false, or range.end if it is true everywhere. The predicate
+// must be true on a prefix of the range and false on the rest.
+pub fn partition_point<F>(range: Range<i64>, predicate: F) -> i64
+where
+    F: Fn(i64) -> bool,
+{
+    let (mut low, mut high) = (range.start, range.end.max(range.start));
+    while low < high {
+        // Half the distance in u64, which can't overflow even for the whole i64 range.
+        let middle = low + (high.wrapping_sub(low) as u64 / 2) as i64;
+        if predicate(middle) {
+            low = middle + 1;
+        } else {
+            high = middle;
+        }
+    }
+
+    low
+}
+
+// Returns where a unimodal function is largest in range, or None if the range is empty. Strictly monotone functions
+// count as unimodal, with the peak at one end.
+pub fn peak<T, F>(range: Range<i64>, f: F) -> Option<i64>
+where
+    T: PartialOrd,
+    F: Fn(i64) -> T,
+{
+    if range.start >= range.end {
+        return None;
+    }
+
+    // Still rising at x, so the peak is past it.
+    Some(partition_point(range.start..range.end - 1, |x| f(x) < f(x + 1)))
+}
+
+// Returns where a unimodal function is largest in range, or None if the range is empty.
+pub fn ternary_search<T, F>(range: Range<i64>, f: F) -> Option<i64>
+where
+    T: PartialOrd,
+    F: Fn(i64) -> T,
+{
+    if range.start >= range.end {
+        return None;
+    }
+
+    // Bounds are inclusive, and wide enough that no difference overflows.
+    let (mut low, mut high) = (range.start as i128, range.end as i128 - 1);
+    while high - low >= 3 {
+        let third = (high - low) / 3;
+        let (left, right) = (low + third, high - third);
+        if f(left as i64) < f(right as i64) {
+            low = left + 1;
+        } else {
+            high = right - 1;
+        }
+    }
+
+    // At most 3 candidates left.
+    let mut best = low;
+    for x in low + 1..=high {
+        if f(best as i64) < f(x as i64) {
+            best = x;
+        }
+    }
+    Some(best as i64)
+}
+
+// Returns where a unimodal function is largest in range, or None if the range is empty.
+pub fn golden_section_search<T, F>(range: Range<i64>, f: F) -> Option<i64>
+where
+    T: PartialOrd,
+    F: Fn(i64) -> T,
+{
+    if range.start >= range.end {
+        return None;
+    }
+
+    // Offsets from the start of the range. Points past the end are lower than every value (None), which keeps the
+    // function unimodal.
+    let length = range.end as i128 - range.start as i128;
+    let value = |offset: i128| match offset < length {
+        true => Some(f((range.start as i128 + offset) as i64)),
+        false => None,
+    };
+
+    // The peak lies strictly between low and high, and high - low is always a Fibonacci number.
+    let mut fibonacci = vec![1i128, 1];
+    while fibonacci[fibonacci.len() - 1] < length + 1 {
+        fibonacci.push(fibonacci[fibonacci.len() - 1] + fibonacci[fibonacci.len() - 2]);
+    }
+
+    let mut k = fibonacci.len() - 1;
+    let (mut low, mut high) = (-1i128, fibonacci[k] - 1);
+    let (mut left, mut right) = (low + fibonacci[k - 2], low + fibonacci[k - 1]);
+    let (mut left_value, mut right_value) = (value(left), value(right));
+
+    while k > 3 {
+        k -= 1;
+        // Ties only happen past the end, where going left is right.
+        if left_value < right_value {
+            low = left;
+            left = right;
+            left_value = right_value;
+            right = low + fibonacci[k - 1];
+            right_value = value(right);
+        } else {
+            high = right;
+            right = left;
+            right_value = left_value;
+            left = low + fibonacci[k - 2];
+            left_value = value(left);
+        }
+    }
+
+    // Down to two candidates, the last two probes.
+    let best = match (k, left_value < right_value) {
+        (3, true) => right,
+        (3, false) => left,
+        _ => low + 1,
+    };
+    debug_assert!(low < best && best < high);
+    Some((range.start as i128 + best) as i64)
+}
+
+// Returns the index of the target in an array that strictly increases and then strictly decreases, or None if it is
+// not there.
+pub fn bitonic_search<T: Ord>(array: &[T], target: &T) -> Option<usize> {
+    let top = peak(0..array.len() as i64, |i| &array[i as usize])? as usize;
+
+    // Binary search the increasing part, then the decreasing part.
+    let rising = partition_point(0..top as i64 + 1, |i| array[i as usize] < *target) as usize;
+    if rising <= top && array[rising] == *target {
+        return Some(rising);
+    }
+
+    let falling = partition_point(top as i64 + 1..array.len() as i64, |i| array[i as usize] > *target) as usize;
+    match array.get(falling).map(|value| value.cmp(target)) {
+        Some(Ordering::Equal) => Some(falling),
+        _ => None,
+    }
+}
+
+fn main() {
+    use std::cell::Cell;
+    use std::cmp::Reverse;
+
+    // Monotone cost: the fewest machines that finish 10^6 jobs of 37 units within 4000 units of time.
+    let machines = partition_point(1..1_000_000, |count| (1_000_000 + count - 1) / count * 37 > 4000);
+    assert!((1_000_000 + machines - 1) / machines * 37 <= 4000);
+    assert!((1_000_000 + machines - 2) / (machines - 1) * 37 > 4000);
+    println!("Machines needed = {}", machines);
+
+    // Whole i64 range, without overflow.
+    assert_eq!(partition_point(i64::MIN..i64::MAX, |x| x < -5), -5);
+    assert_eq!(partition_point(0..10, |_| true), 10);
+    let (start, end) = (10, 0);
+    assert_eq!(partition_point(start..end, |_| true), 10);
+
+    // Every peak position of every length, counting evaluations.
+    let evaluations = Cell::new(0u64);
+    let mut most = [0u64; 3];
+    for length in 1..=200i64 {
+        let log_length = (64 - (length as u64).leading_zeros()) as u64;
+        for top in 0..length {
+            let f = |x: i64| {
+                assert!((0..length).contains(&x), "Evaluated outside the range");
+                evaluations.set(evaluations.get() + 1);
+                if x <= top {
+                    x
+                } else {
+                    2 * top - x
+                }
+            };
+
+            for (index, most_evaluations) in most.iter_mut().enumerate() {
+                evaluations.set(0);
+                let found = match index {
+                    0 => peak(0..length, f),
+                    1 => ternary_search(0..length, f),
+                    _ => golden_section_search(0..length, f),
+                };
+                assert_eq!(
+                    found,
+                    Some(top),
+                    "Search {} missed the peak of {} values",
+                    index,
+                    length
+                );
+                *most_evaluations = (*most_evaluations).max(evaluations.get());
+            }
+            assert!(
+                evaluations.get() <= 3 * log_length / 2 + 3,
+                "Too many golden section probes"
+            );
+        }
+    }
+    println!(
+        "Peaks of up to 200 values in at most {} (binary), {} (ternary), {} (golden section) evaluations",
+        most[0], most[1], most[2]
+    );
+
+    // Convex cost over floats, minimized through Reverse.
+    let cost = |x: i64| Reverse((x as f64 - 1234.4).powi(2) + 7.0);
+    assert_eq!(golden_section_search(-1_000_000..1_000_000, cost), Some(1234));
+    assert_eq!(ternary_search(-1_000_000..1_000_000, cost), Some(1234));
+    assert_eq!(
+        golden_section_search(i64::MIN..i64::MAX, |x| Reverse(x.unsigned_abs())),
+        Some(0)
+    );
+    assert_eq!(peak(5..5, |x| x), None);
+
+    // Every element of bitonic arrays, and values between them.
+    for length in 0..=40usize {
+        for top in 0..length.max(1) {
+            let array = (0..length as i32)
+                .map(|i| {
+                    if i as usize <= top {
+                        2 * i
+                    } else {
+                        2 * (2 * top as i32 - i) + 1
+                    }
+                })
+                .collect::<Vec<i32>>();
+            for target in -4 * length as i32 - 3..=4 * length as i32 + 2 {
+                let index = bitonic_search(&array, &target);
+                assert_eq!(
+                    index,
+                    array.iter().position(|&value| value == target),
+                    "Failed on {:?}",
+                    array
+                );
+            }
+        }
+    }
+
+    let array = [1, 3, 8, 12, 4, 2];
+    println!(
+        "Bitonic {:?}: 4 at {:?}, 5 at {:?}",
+        array,
+        bitonic_search(&array, &4),
+        bitonic_search(&array, &5)
+    );
+}
@@ -0,0 +1,203 @@
+/**
+ * Tournament tree (winner tree).
+ *
+ * Leaves hold the players, and every internal node holds the winner of the match between its two children, so the
+ * root holds the largest element after n - 1 comparisons. Works for any number of leaves: nodes are numbered like a
+ * binary heap, matches are nodes 1..n and leaf i is node n + i, which keeps every leaf within ⌈log n⌉ matches of the
+ * root.
+ *
+ * Replacing a leaf only re-runs the matches on its path to the root, at most ⌈log n⌉ comparisons. Removing the winner
+ * that way gives the next largest element, so the top k elements take n - 1 + (k - 1)⌈log n⌉ comparisons. Replacing
+ * the winner with the next element of its list is k-way merging (with std::cmp::Reverse to get the smallest first).
+ */
+use std::mem;
+
+pub struct TournamentTree<T> {
+    // Players, None once removed. Removed players lose every match.
+    leaves: Vec<Option<T>>,
+    // Winning leaf of every match, match 0 is unused.
+    winners: Vec<usize>,
+    comparisons: u64,
+}
+
+impl<T: Ord> TournamentTree<T> {
+    // Plays the whole tournament between the elements.
+    pub fn new(elements: Vec<T>) -> TournamentTree<T> {
+        TournamentTree::from_leaves(elements.into_iter().map(Some).collect())
+    }
+
+    // Plays the whole tournament between leaves that may be empty, like the heads of lists to merge.
+    pub fn from_leaves(leaves: Vec<Option<T>>) -> TournamentTree<T> {
+        let length = leaves.len();
+        let mut tree = TournamentTree {
+            leaves,
+            winners: vec![0; length],
+            comparisons: 0,
+        };
+
+        // Children are played before their parents.
+        for node in (1..length).rev() {
+            tree.winners[node] = tree.play(node);
+        }
+
+        tree
+    }
+
+    // Number of leaves, removed ones included.
+    pub fn len(&self) -> usize {
+        self.leaves.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.leaves.is_empty()
+    }
+
+    // Element comparisons made so far.
+    pub fn comparisons(&self) -> u64 {
+        self.comparisons
+    }
+
+    // Returns the leaf index and value of the largest remaining element, or None if no element is left.
+    pub fn winner(&self) -> Option<(usize, &T)> {
+        let leaf = match self.len() {
+            0 => return None,
+            1 => 0,
+            _ => self.winners[1],
+        };
+
+        self.leaves[leaf].as_ref().map(|value| (leaf, value))
+    }
+
+    // Returns the current value of a leaf.
+    pub fn leaf(&self, leaf: usize) -> Option<&T> {
+        self.leaves[leaf].as_ref()
+    }
+
+    // Puts a new value in a leaf (None removes it), re-runs the matches above it, and returns the old value.
+    pub fn replace(&mut self, leaf: usize, value: Option<T>) -> Option<T> {
+        let old_value = mem::replace(&mut self.leaves[leaf], value);
+
+        let mut node = (self.len() + leaf) / 2;
+        while node > 0 {
+            self.winners[node] = self.play(node);
+            node /= 2;
+        }
+
+        old_value
+    }
+
+    // Removes the largest remaining element, and returns its leaf index and value.
+    pub fn pop(&mut self) -> Option<(usize, T)> {
+        let (leaf, _) = self.winner()?;
+        self.replace(leaf, None).map(|value| (leaf, value))
+    }
+
+    // Returns the k largest elements in descending order, in n - 1 + (k - 1)⌈log n⌉ comparisons overall.
+    // Consumes the tree, which saves re-running the matches after taking the last one out.
+    pub fn top_k(mut self, k: usize) -> Vec<T> {
+        let mut top = Vec::with_capacity(k);
+        while top.len() + 1 < k {
+            match self.pop() {
+                Some((_, value)) => top.push(value),
+                None => return top,
+            }
+        }
+
+        if k > 0 {
+            if let Some((leaf, _)) = self.winner() {
+                top.extend(self.leaves[leaf].take());
+            }
+        }
+
+        top
+    }
+
+    // Returns the leaf that wins the match at [node]. Ties go to the left child.
+    fn play(&mut self, node: usize) -> usize {
+        let (left, right) = (self.get_winner(2 * node), self.get_winner(2 * node + 1));
+
+        match (&self.leaves[left], &self.leaves[right]) {
+            (Some(left_value), Some(right_value)) => {
+                self.comparisons += 1;
+                if left_value >= right_value {
+                    left
+                } else {
+                    right
+                }
+            }
+            (None, Some(_)) => right,
+            _ => left,
+        }
+    }
+
+    // Leaf that won at [node], a leaf node wins by itself.
+    fn get_winner(&self, node: usize) -> usize {
+        if node >= self.len() {
+            node - self.len()
+        } else {
+            self.winners[node]
+        }
+    }
+}
+
+fn main() {
+    use std::cmp::Reverse;
+
+    // Top k of every length, checked against sorting, within the comparison bound.
+    for length in 1..=100usize {
+        let elements = (0..length).map(|i| (i * 37 + 11) % 101).collect::<Vec<usize>>();
+        let mut sorted = elements.clone();
+        sorted.sort_unstable_by(|a, b| b.cmp(a));
+
+        let log_length = (usize::BITS - (length - 1).leading_zeros()) as u64;
+        for k in 0..=length {
+            let tree = TournamentTree::new(elements.clone());
+            let build_comparisons = tree.comparisons();
+            assert_eq!(build_comparisons, length as u64 - 1);
+
+            // Count comparisons through a second tree, since top_k consumes the first one.
+            let mut counted = TournamentTree::new(elements.clone());
+            for _ in 1..k {
+                counted.pop();
+            }
+            let bound = length as u64 - 1 + (k.max(1) as u64 - 1) * log_length;
+            assert!(
+                counted.comparisons() <= bound,
+                "Too many comparisons for top {} of {}",
+                k,
+                length
+            );
+
+            assert_eq!(tree.top_k(k), sorted[..k].to_vec());
+        }
+    }
+    println!("Top k verified for every k up to 100 elements");
+
+    // Top 3 of 10 elements.
+    let tree = TournamentTree::new(vec![31, 41, 59, 26, 53, 58, 97, 93, 23, 84]);
+    println!("Top 3 = {:?}", tree.top_k(3));
+
+    // K-way merge, every leaf is the head of a sorted list, smallest first.
+    let lists = vec![vec![1, 4, 9], vec![2, 3, 10, 11], vec![], vec![5, 6, 7, 8]];
+    let mut positions = vec![0; lists.len()];
+    let mut tree = TournamentTree::from_leaves(
+        lists
+            .iter()
+            .map(|list| list.first().map(|&value| Reverse(value)))
+            .collect(),
+    );
+
+    let mut merged = Vec::new();
+    while let Some((leaf, &Reverse(value))) = tree.winner() {
+        merged.push(value);
+        positions[leaf] += 1;
+        tree.replace(leaf, lists[leaf].get(positions[leaf]).map(|&next| Reverse(next)));
+    }
+    assert_eq!(merged, (1..=11).collect::<Vec<i32>>());
+    println!(
+        "Merged {:?} = {:?} in {} comparisons",
+        lists,
+        merged,
+        tree.comparisons()
+    );
+}
@@ -0,0 +1,31 @@
+/**
+ * Point in the 2D plane, shared by closest pair and the travelling salesman problems.
+ */
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Point {
+    pub x: f64,
+    pub y: f64,
+}
+
+impl Point {
+    pub fn new(x: f64, y: f64) -> Point {
+        Point { x, y }
+    }
+
+    // Return squared euclidean distance, enough to compare distances without a square root.
+    pub fn squared_euclidean_distance(&self, other: &Point) -> f64 {
+        (self.x - other.x).powi(2) + (self.y - other.y).powi(2)
+    }
+
+    // Return euclidean distance.
+    pub fn euclidean_distance(&self, other: &Point) -> f64 {
+        self.squared_euclidean_distance(other).sqrt()
+    }
+}
+
+fn main() {
+    let (a, b) = (Point::new(1.0, 2.0), Point::new(4.0, 6.0));
+    assert_eq!(a.squared_euclidean_distance(&b), 25.0);
+    println!("Distance between {:?} and {:?} = {}", a, b, a.euclidean_distance(&b));
+}
@@ -0,0 +1,320 @@
+/**
+ * External merge sort, for integer files larger than memory.
+ *
+ * The input is read in chunks that fit the memory limit, every chunk is sorted with quick sort and written to a
+ * temporary run file, and the runs are merged k ways with a min heap. When there are too many runs to open at once,
+ * they are merged in several passes.
+ *
+ * Input and output files have one integer per line.
+ */
+use std::cmp::max;
+use std::env;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, BufWriter, Error, ErrorKind, Read, Write};
+use std::mem;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[allow(dead_code)]
+mod quick_sort;
+use quick_sort::min_heap::Heap;
+use quick_sort::rng::Rng;
+use quick_sort::{quick_sort, Partition, PivotType, SortOptions, SortStats};
+
+// Most runs merged at once, to stay well below the open file limit.
+const MAX_FAN_IN: usize = 64;
+
+// Smallest read buffer for a run, no matter how low the memory limit is.
+const MIN_BUFFER_SIZE: usize = 4096;
+
+// Default memory limit, 64 MB.
+const DEFAULT_MEMORY_LIMIT: usize = 64 << 20;
+
+// Used to give every run file a unique name.
+static RUN_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+// Work done by an external sort.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ExternalSortStats {
+    // Number of integers sorted.
+    pub integers: u64,
+    // Number of sorted runs written from the input.
+    pub runs: usize,
+    // Number of times the runs were merged, the last pass writes the output.
+    pub merge_passes: usize,
+    // Quick sort stats summed over all chunks.
+    pub sort: SortStats,
+}
+
+// Temporary file holding a sorted run as little endian i64 values. It is deleted once dropped.
+struct RunFile {
+    path: PathBuf,
+}
+
+impl RunFile {
+    fn new() -> RunFile {
+        let nanos = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_nanos();
+        let name = format!(
+            "external_sort_{}_{}_{}.run",
+            process::id(),
+            nanos,
+            RUN_COUNTER.fetch_add(1, Ordering::Relaxed)
+        );
+
+        RunFile {
+            path: env::temp_dir().join(name),
+        }
+    }
+}
+
+impl Drop for RunFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+// Sorts the integers in the input file into the output file, using about [memory_limit] bytes of memory.
+pub fn external_sort(
+    input: &Path,
+    output: &Path,
+    memory_limit: usize,
+    options: &SortOptions,
+    rng: &mut Rng,
+) -> Result<ExternalSortStats, Error> {
+    let mut stats = ExternalSortStats::default();
+    let chunk_length = max(1, memory_limit / mem::size_of::<i64>());
+
+    // Split the input into sorted runs.
+    let mut runs = Vec::new();
+    let mut chunk = Vec::with_capacity(chunk_length);
+    for (index, line) in BufReader::new(File::open(input)?).lines().enumerate() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let value = line.parse::<i64>().map_err(|error| {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!("line {}: '{}' is not an integer ({})", index + 1, line, error),
+            )
+        })?;
+        chunk.push(value);
+
+        if chunk.len() == chunk_length {
+            runs.push(write_run(&mut chunk, options, rng, &mut stats)?);
+        }
+    }
+    if !chunk.is_empty() {
+        runs.push(write_run(&mut chunk, options, rng, &mut stats)?);
+    }
+    stats.runs = runs.len();
+
+    // Free the chunk before merging, the read buffers get its memory instead.
+    drop(chunk);
+
+    // Too many runs to open at once, merge them in groups into longer runs.
+    while runs.len() > MAX_FAN_IN {
+        let buffer_size = get_buffer_size(memory_limit, MAX_FAN_IN);
+        let mut merged_runs = Vec::new();
+        for group in runs.chunks(MAX_FAN_IN) {
+            let merged_run = RunFile::new();
+            let mut writer = BufWriter::with_capacity(buffer_size, File::create(&merged_run.path)?);
+            merge_runs(group, buffer_size, &mut |value| writer.write_all(&value.to_le_bytes()))?;
+            writer.flush()?;
+            merged_runs.push(merged_run);
+        }
+
+        // Dropping the old runs deletes their files.
+        runs = merged_runs;
+        stats.merge_passes += 1;
+    }
+
+    // Final merge writes the output.
+    let buffer_size = get_buffer_size(memory_limit, runs.len());
+    let mut writer = BufWriter::with_capacity(buffer_size, File::create(output)?);
+    merge_runs(&runs, buffer_size, &mut |value| writeln!(writer, "{}", value))?;
+    writer.flush()?;
+    stats.merge_passes += 1;
+
+    Ok(stats)
+}
+
+// Sorts the chunk, writes it to a new run file and empties it.
+fn write_run(
+    chunk: &mut Vec<i64>,
+    options: &SortOptions,
+    rng: &mut Rng,
+    stats: &mut ExternalSortStats,
+) -> Result<RunFile, Error> {
+    let sort_stats = quick_sort(chunk, options, rng);
+    stats.integers += chunk.len() as u64;
+    stats.sort.comparisons += sort_stats.comparisons;
+    stats.sort.swaps += sort_stats.swaps;
+    stats.sort.max_depth = max(stats.sort.max_depth, sort_stats.max_depth);
+
+    let run = RunFile::new();
+    let mut writer = BufWriter::new(File::create(&run.path)?);
+    for value in chunk.iter() {
+        writer.write_all(&value.to_le_bytes())?;
+    }
+    writer.flush()?;
+
+    chunk.clear();
+    Ok(run)
+}
+
+// Splits the memory limit between the read buffers of the runs and the write buffer.
+fn get_buffer_size(memory_limit: usize, runs: usize) -> usize {
+    max(MIN_BUFFER_SIZE, memory_limit / (runs + 1))
+}
+
+// Merges sorted runs, handing every value to [emit] in ascending order.
+fn merge_runs(
+    runs: &[RunFile],
+    buffer_size: usize,
+    emit: &mut dyn FnMut(i64) -> Result<(), Error>,
+) -> Result<(), Error> {
+    let mut readers = Vec::with_capacity(runs.len());
+    for run in runs {
+        readers.push(BufReader::with_capacity(buffer_size, File::open(&run.path)?));
+    }
+
+    // Heap holds the smallest unmerged value of every run, along with the run it came from.
+    let mut heap = Heap::new();
+    for (index, reader) in readers.iter_mut().enumerate() {
+        if let Some(value) = read_value(reader)? {
+            heap.insert((value, index));
+        }
+    }
+
+    // Smallest value overall goes out, and is replaced by the next value of the same run.
+    while let Some((value, index)) = heap.extract() {
+        emit(value)?;
+        if let Some(next_value) = read_value(&mut readers[index])? {
+            heap.insert((next_value, index));
+        }
+    }
+
+    Ok(())
+}
+
+// Reads the next value of a run, or None at the end of the run.
+fn read_value(reader: &mut BufReader<File>) -> Result<Option<i64>, Error> {
+    let mut bytes = [0; 8];
+    match reader.read_exact(&mut bytes) {
+        Ok(()) => Ok(Some(i64::from_le_bytes(bytes))),
+        Err(error) if error.kind() == ErrorKind::UnexpectedEof => Ok(None),
+        Err(error) => Err(error),
+    }
+}
+
+// Parses a size like 4096, 512K, 64M or 1G into bytes.
+fn parse_size(size: &str) -> Option<usize> {
+    let (digits, multiplier) = match size.chars().last()?.to_ascii_uppercase() {
+        'K' => (&size[..size.len() - 1], 1 << 10),
+        'M' => (&size[..size.len() - 1], 1 << 20),
+        'G' => (&size[..size.len() - 1], 1 << 30),
+        _ => (size, 1),
+    };
+
+    digits.parse::<usize>().ok()?.checked_mul(multiplier)
+}
+
+// Sorts generated integers with several memory limits, and checks the output against an in-memory sort.
+fn self_check(options: &SortOptions, memory_limits: &[usize], rng: &mut Rng) -> Result<(), Error> {
+    let length = 1_000_000;
+
+    // Random integers, with plenty of negatives and duplicates.
+    println!("Seed = {}", rng.seed());
+    let mut values = Vec::with_capacity(length);
+    for _ in 0..length {
+        values.push(rng.gen_range(0..2_000_000) as i64 - 1_000_000);
+    }
+
+    let input = RunFile::new();
+    let output = RunFile::new();
+    let mut writer = BufWriter::new(File::create(&input.path)?);
+    for value in &values {
+        writeln!(writer, "{}", value)?;
+    }
+    writer.flush()?;
+
+    values.sort_unstable();
+
+    for &memory_limit in memory_limits {
+        let stats = external_sort(&input.path, &output.path, memory_limit, options, rng)?;
+
+        let mut sorted_values = Vec::with_capacity(length);
+        for line in BufReader::new(File::open(&output.path)?).lines() {
+            sorted_values.push(line?.parse::<i64>().expect("Output is not an integer"));
+        }
+        assert!(sorted_values == values, "External sort differs from in-memory sort");
+
+        println!(
+            "Memory limit {} bytes: {} integers, {} runs, {} merge passes, verified against in-memory sort",
+            memory_limit, stats.integers, stats.runs, stats.merge_passes
+        );
+    }
+
+    Ok(())
+}
+
+fn main() -> Result<(), Error> {
+    let usage = "usage: ./external_sort [<input file> <output file>] [--memory-limit <bytes, or with K, M, G suffix>] \
+                 [--seed <seed>]";
+
+    // Parse arguments.
+    let mut args = env::args().skip(1).collect::<Vec<String>>();
+    let mut rng = Rng::from_args(&mut args).unwrap_or_else(|| {
+        eprintln!("{}", usage);
+        process::exit(1);
+    });
+    let mut files = Vec::new();
+    let mut memory_limit = None;
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == "--memory-limit" {
+            memory_limit = args.next().as_deref().and_then(parse_size);
+            if memory_limit.is_none() {
+                eprintln!("{}", usage);
+                process::exit(1);
+            }
+        } else {
+            files.push(arg);
+        }
+    }
+
+    let options = SortOptions {
+        partition: Partition::ThreeWay,
+        introsort: true,
+        ..SortOptions::new(PivotType::NintherTukey)
+    };
+
+    match files.len() {
+        // No files given, check against generated data instead. Small limits force many runs and merge passes.
+        0 => self_check(
+            &options,
+            &memory_limit.map_or(vec![8 << 10, 256 << 10, 64 << 20], |limit| vec![limit]),
+            &mut rng,
+        ),
+        2 => {
+            let stats = external_sort(
+                Path::new(&files[0]),
+                Path::new(&files[1]),
+                memory_limit.unwrap_or(DEFAULT_MEMORY_LIMIT),
+                &options,
+                &mut rng,
+            )?;
+            println!("{:?}", stats);
+            Ok(())
+        }
+        _ => {
+            eprintln!("{}", usage);
+            process::exit(1);
+        }
+    }
+}
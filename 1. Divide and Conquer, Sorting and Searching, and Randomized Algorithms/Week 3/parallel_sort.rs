@@ -0,0 +1,164 @@
+/**
+ * Parallel sorting with scoped threads.
+ *
+ * Merge sort sorts both halves on separate threads and merges them on the current one, quick sort (see quick_sort.rs)
+ * does the same with both sides of a partition. Below a size cutoff, or once every core is busy, sorting goes on in
+ * the current thread.
+ *
+ * Running this file benchmarks both against slice::sort_unstable on random integers.
+ */
+use std::env;
+use std::process;
+use std::thread;
+use std::time::Instant;
+
+#[allow(dead_code)]
+mod quick_sort;
+use quick_sort::rng::Rng;
+use quick_sort::{parallel_quick_sort, quick_sort, Partition, PivotType, SortOptions, PARALLEL_CUTOFF};
+
+// Sorts the array using merge sort.
+pub fn merge_sort<T: Ord + Clone>(array: &mut [T]) {
+    // Additional space needed to help merge sorted halves.
+    let mut temp = array.to_vec();
+    sort(array, &mut temp);
+}
+
+// Sorts the array using merge sort on all cores.
+pub fn parallel_merge_sort<T: Ord + Clone + Send>(array: &mut [T]) {
+    let threads = thread::available_parallelism().map_or(1, |threads| threads.get());
+    let mut temp = array.to_vec();
+    parallel_sort(array, &mut temp, threads);
+}
+
+// Recursively sort and merge, temp must be as long as array.
+fn sort<T: Ord + Clone>(array: &mut [T], temp: &mut [T]) {
+    if array.len() <= 1 {
+        return;
+    }
+
+    // Sort left half, and right half.
+    let middle = array.len() / 2;
+    {
+        let (left, right) = array.split_at_mut(middle);
+        let (temp_left, temp_right) = temp.split_at_mut(middle);
+        sort(left, temp_left);
+        sort(right, temp_right);
+    }
+
+    // Merge both halves.
+    merge(array, middle, temp);
+}
+
+// Recursively sort both halves on separate threads, using at most [threads] threads.
+fn parallel_sort<T: Ord + Clone + Send>(array: &mut [T], temp: &mut [T], threads: usize) {
+    // Not worth splitting, sort on this thread.
+    if threads <= 1 || array.len() < PARALLEL_CUTOFF {
+        sort(array, temp);
+        return;
+    }
+
+    let middle = array.len() / 2;
+    {
+        let (left, right) = array.split_at_mut(middle);
+        let (temp_left, temp_right) = temp.split_at_mut(middle);
+        let left_threads = threads / 2;
+        thread::scope(|scope| {
+            scope.spawn(move || parallel_sort(left, temp_left, left_threads));
+            parallel_sort(right, temp_right, threads - left_threads);
+        });
+    }
+
+    merge(array, middle, temp);
+}
+
+// Merges the sorted halves array[..middle] and array[middle..].
+fn merge<T: Ord + Clone>(array: &mut [T], middle: usize, temp: &mut [T]) {
+    let (mut index1, mut index2, mut temp_index) = (0, middle, 0);
+
+    // Copy smallest elements first from both sorted halves. Ties go left, which keeps the sort stable.
+    while index1 < middle && index2 < array.len() {
+        if array[index2] < array[index1] {
+            temp[temp_index] = array[index2].clone();
+            index2 += 1;
+        } else {
+            temp[temp_index] = array[index1].clone();
+            index1 += 1;
+        }
+        temp_index += 1;
+    }
+
+    // Copy remaining elements.
+    temp[temp_index..temp_index + middle - index1].clone_from_slice(&array[index1..middle]);
+    temp_index += middle - index1;
+    temp[temp_index..].clone_from_slice(&array[index2..]);
+
+    // Temp array has the merged sorted values, copy them back into original array.
+    array.clone_from_slice(temp);
+}
+
+// Runs [sort_function] on a copy of input, checks it against the expected result and prints the time taken.
+fn benchmark<F: FnMut(&mut [i32])>(name: &str, input: &[i32], expected: &[i32], mut sort_function: F) {
+    let mut array = input.to_vec();
+
+    let start = Instant::now();
+    sort_function(&mut array);
+    let elapsed = start.elapsed();
+
+    assert!(array == expected, "{} did not sort the array", name);
+    println!("{:<28} {:>10.3} s", name, elapsed.as_secs_f64());
+}
+
+fn main() {
+    // Ensure proper usage.
+    let mut args = env::args().skip(1).collect::<Vec<String>>();
+    let mut rng = match Rng::from_args(&mut args) {
+        Some(rng) if args.len() <= 1 => rng,
+        _ => {
+            eprintln!("usage: ./parallel_sort [number of integers] [--seed <seed>]");
+            process::exit(1);
+        }
+    };
+
+    let length = match args.first() {
+        Some(arg) => arg.parse::<usize>().expect("Failed to parse number of integers"),
+        None => 10_000_000,
+    };
+
+    // Random integers.
+    let input = (0..length).map(|_| rng.next_u64() as i32).collect::<Vec<i32>>();
+
+    println!(
+        "Sorting {} integers on {} threads, seed = {}",
+        length,
+        thread::available_parallelism().map_or(1, |threads| threads.get()),
+        rng.seed()
+    );
+
+    // Reference result.
+    let mut expected = input.clone();
+    let start = Instant::now();
+    expected.sort_unstable();
+    println!(
+        "{:<28} {:>10.3} s",
+        "slice::sort_unstable",
+        start.elapsed().as_secs_f64()
+    );
+
+    let options = SortOptions {
+        partition: Partition::ThreeWay,
+        introsort: true,
+        ..SortOptions::new(PivotType::NintherTukey)
+    };
+
+    benchmark("quick_sort", &input, &expected, |array| {
+        quick_sort(array, &options, &mut rng);
+    });
+    benchmark("parallel_quick_sort", &input, &expected, |array| {
+        parallel_quick_sort(array, &options, &mut rng);
+    });
+    benchmark("merge_sort", &input, &expected, merge_sort);
+    benchmark("parallel_merge_sort", &input, &expected, |array| {
+        parallel_merge_sort(array)
+    });
+}
@@ -1,109 +1,503 @@
 /**
  * Quick Sort, example of randomized algorithms.
+ *
+ * Sorts any type with a comparator, and reports the number of comparisons and swaps done along with the deepest
+ * recursion level. Recursion always goes into the smaller partition and loops over the larger one, so the stack
+ * stays O(log n) deep even with bad pivots (like the first element of a sorted array).
+ *
+ * Three-way partitioning keeps runs of duplicates out of the recursion, and introsort falls back to heap sort once
+ * the recursion gets deeper than 2.log n, which bounds the running time to O(n log n) on any input.
+ *
+ * The parallel variant sorts both sides of a partition on separate threads, until partitions get smaller than a
+ * cutoff or every core is busy.
  */
-use std::io::stdin;
+use std::cmp::{max, Ordering};
+use std::env;
+use std::io::{stdin, BufRead};
+use std::mem;
+use std::process;
+use std::thread;
+
+// Partitions smaller than this are not worth the cost of a new thread.
+pub const PARALLEL_CUTOFF: usize = 1 << 15;
+
+#[allow(dead_code)]
+#[path = "../../2. Graph Search, Shortest Paths, and Data Structures/Week 3/min_heap.rs"]
+pub mod min_heap;
+
+#[allow(dead_code)]
+#[path = "rng.rs"]
+pub mod rng;
+use self::rng::Rng;
 
 // Pivot type to choose for partition.
-enum PivotType {
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PivotType {
     First,
     Last,
+    // Median of the first, middle and last elements.
     MedianOfThree,
+    // Uniformly random element.
+    Random,
+    // Tukey's ninther, median of the medians of three groups of three evenly spaced elements.
+    NintherTukey,
 }
 
-// Sorts the array using quick sort, and returns number of comparisons done.
-fn quick_sort(arr: &mut [i32], pivot_type: &PivotType) -> i32 {
-    // Base case, array with zero or one item is already sorted.
-    if arr.len() <= 1 {
-        return 0;
+// How elements are split around the pivot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Partition {
+    // Smaller elements to the left of a wall, everything else to the right.
+    Wall,
+    // Dutch national flag, smaller, equal and larger elements. Equal elements are never looked at again.
+    ThreeWay,
+}
+
+// How comparisons are counted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CountingMode {
+    // Every call to the comparator, including the ones made to choose a pivot.
+    Actual,
+    // Course convention, length - 1 comparisons for every partition call and choosing the pivot is free.
+    Course,
+}
+
+// Settings for a single sort.
+#[derive(Debug, Clone, Copy)]
+pub struct SortOptions {
+    pub pivot_type: PivotType,
+    pub partition: Partition,
+    // Switch to heap sort once the recursion is deeper than 2.log n.
+    pub introsort: bool,
+    pub counting: CountingMode,
+}
+
+impl SortOptions {
+    // Options for [pivot_type] with the wall partition, counting every comparison.
+    pub fn new(pivot_type: PivotType) -> SortOptions {
+        SortOptions {
+            pivot_type,
+            partition: Partition::Wall,
+            introsort: false,
+            counting: CountingMode::Actual,
+        }
     }
+}
 
-    // Choose first item as pivot index.
-    let pivot_index: usize = get_pivot_index(&arr, &pivot_type);
+// Work done by a sort.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SortStats {
+    pub comparisons: u64,
+    pub swaps: u64,
+    // Number of nested partition calls on the deepest path, 0 if there was nothing to partition.
+    pub max_depth: usize,
+}
 
-    // Bring pivot to first place of array.
-    arr.swap(0, pivot_index);
+// Sorts the array in ascending order using quick sort. Random pivots are drawn from rng.
+pub fn quick_sort<T: Ord>(arr: &mut [T], options: &SortOptions, rng: &mut Rng) -> SortStats {
+    quick_sort_by(arr, options, rng, |a, b| a.cmp(b))
+}
 
-    // Start of the wall.
-    let mut wall: usize = 0;
+// Sorts the array using quick sort with the given comparator.
+pub fn quick_sort_by<T, F>(arr: &mut [T], options: &SortOptions, rng: &mut Rng, compare: F) -> SortStats
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let mut sorter = Sorter::new(compare, options, get_depth_limit(arr.len()), rng);
+    sorter.sort(arr, 1);
+    sorter.stats
+}
 
-    for index in 1..arr.len() {
-        if arr[index] < arr[0] {
-            // Move wall to make more space.
-            wall += 1;
-            // Bring element to left of wall.
-            arr.swap(index, wall);
-        }
+// Sorts the array in ascending order using quick sort on all cores.
+pub fn parallel_quick_sort<T: Ord + Send>(arr: &mut [T], options: &SortOptions, rng: &mut Rng) -> SortStats {
+    parallel_quick_sort_by(arr, options, rng, |a, b| a.cmp(b))
+}
+
+// Sorts the array using quick sort on all cores with the given comparator.
+pub fn parallel_quick_sort_by<T, F>(arr: &mut [T], options: &SortOptions, rng: &mut Rng, compare: F) -> SortStats
+where
+    T: Send,
+    F: Fn(&T, &T) -> Ordering + Sync,
+{
+    let threads = thread::available_parallelism().map_or(1, |threads| threads.get());
+    let depth_limit = get_depth_limit(arr.len());
+    parallel_sort(arr, options, &compare, depth_limit, 1, rng, threads)
+}
+
+// Sorts arr, which is [depth] partition calls deep, using at most [threads] threads.
+fn parallel_sort<T, F>(
+    arr: &mut [T],
+    options: &SortOptions,
+    compare: &F,
+    depth_limit: usize,
+    depth: usize,
+    rng: &mut Rng,
+    threads: usize,
+) -> SortStats
+where
+    T: Send,
+    F: Fn(&T, &T) -> Ordering + Sync,
+{
+    let mut sorter = Sorter::new(compare, options, depth_limit, rng);
+
+    // Not worth splitting, sort on this thread.
+    if threads <= 1 || arr.len() < PARALLEL_CUTOFF {
+        sorter.sort(arr, depth);
+        return sorter.stats;
     }
 
-    // Bring pivot to its rightful position.
-    arr.swap(0, wall);
+    let (left, right) = match sorter.split(arr, depth) {
+        Some(parts) => parts,
+        None => return sorter.stats,
+    };
+
+    // Each side gets its own random pivots and share of the threads.
+    let (mut left_rng, mut right_rng) = (sorter.rng.fork(), sorter.rng.fork());
+    let left_threads = threads / 2;
+    let (left_stats, right_stats) = thread::scope(|scope| {
+        let left_handle = scope.spawn(move || {
+            parallel_sort(
+                left,
+                options,
+                compare,
+                depth_limit,
+                depth + 1,
+                &mut left_rng,
+                left_threads,
+            )
+        });
+        let right_stats = parallel_sort(
+            right,
+            options,
+            compare,
+            depth_limit,
+            depth + 1,
+            &mut right_rng,
+            threads - left_threads,
+        );
+        (left_handle.join().unwrap(), right_stats)
+    });
 
-    // Sort left half of wall.
-    let left_comparisons: i32 = quick_sort(&mut arr[..wall], &pivot_type);
+    SortStats {
+        comparisons: sorter.stats.comparisons + left_stats.comparisons + right_stats.comparisons,
+        swaps: sorter.stats.swaps + left_stats.swaps + right_stats.swaps,
+        max_depth: max(sorter.stats.max_depth, max(left_stats.max_depth, right_stats.max_depth)),
+    }
+}
 
-    // Sort right half of wall.
-    let right_comparisons: i32 = quick_sort(&mut arr[wall + 1..], &pivot_type);
+// Recursion depth at which introsort gives up on quick sort, 2.log n.
+fn get_depth_limit(length: usize) -> usize {
+    2 * (usize::BITS - length.leading_zeros()) as usize
+}
 
-    // Number of comparisons are always equal to len - 1.
-    return left_comparisons + right_comparisons + (arr.len() as i32 - 1);
+// State shared by the recursive calls of a single sort.
+struct Sorter<'a, F> {
+    compare: F,
+    options: &'a SortOptions,
+    // Deepest recursion level before introsort switches to heap sort.
+    depth_limit: usize,
+    stats: SortStats,
+    rng: &'a mut Rng,
 }
 
-// Get the pivot index based on the type we need. (first element, last element, or median of three rule).
-fn get_pivot_index(arr: &[i32], pivot_type: &PivotType) -> usize {
-    match pivot_type {
-        PivotType::First => 0,
-        PivotType::Last => arr.len() - 1,
-        PivotType::MedianOfThree => {
-            let middle_index = arr.len() / 2;
+impl<'a, F> Sorter<'a, F> {
+    fn new(compare: F, options: &'a SortOptions, depth_limit: usize, rng: &'a mut Rng) -> Sorter<'a, F> {
+        Sorter {
+            compare,
+            options,
+            depth_limit,
+            stats: SortStats::default(),
+            rng,
+        }
+    }
+
+    // Sorts arr, which is [depth] partition calls deep.
+    fn sort<T>(&mut self, mut arr: &mut [T], mut depth: usize)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        // Base case, array with zero or one item is already sorted.
+        while arr.len() > 1 {
+            let (left, right) = match self.split(mem::take(&mut arr), depth) {
+                Some(parts) => parts,
+                None => return,
+            };
+
+            // Recurse into the smaller side, and keep looping over the larger one.
+            if left.len() < right.len() {
+                self.sort(left, depth + 1);
+                arr = right;
+            } else {
+                self.sort(right, depth + 1);
+                arr = left;
+            }
+            depth += 1;
+        }
+    }
+
+    // Partitions arr around a pivot, and returns the sides that are still left to sort.
+    // Returns None if the recursion got too deep and introsort heap sorted arr instead.
+    fn split<'s, T>(&mut self, arr: &'s mut [T], depth: usize) -> Option<(&'s mut [T], &'s mut [T])>
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        // Recursion got too deep, pivots must be bad for this input.
+        if self.options.introsort && depth > self.depth_limit {
+            let swaps = min_heap::heap_sort_by(arr, |a, b| self.less(a, b));
+            self.stats.swaps += swaps as u64;
+            return None;
+        }
+
+        self.stats.max_depth = max(self.stats.max_depth, depth);
+        if self.options.counting == CountingMode::Course {
+            self.stats.comparisons += arr.len() as u64 - 1;
+        }
+
+        // Bring pivot to first place of array, and partition around it.
+        let pivot_index = self.get_pivot_index(arr);
+        self.swap(arr, 0, pivot_index);
+        let (left_end, right_start) = match self.options.partition {
+            Partition::Wall => {
+                let wall = self.partition(arr);
+                (wall, wall + 1)
+            }
+            Partition::ThreeWay => self.partition_three_way(arr),
+        };
+
+        let (left, right) = arr.split_at_mut(right_start);
+        Some((&mut left[..left_end], right))
+    }
+
+    // Partitions around the pivot at the first place, and returns the final position of the pivot.
+    fn partition<T>(&mut self, arr: &mut [T]) -> usize
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        // Start of the wall.
+        let mut wall: usize = 0;
 
-            // For element count less then 3, we can't compute median of three.
-            if arr.len() < 3 {
-                return middle_index;
+        for index in 1..arr.len() {
+            if self.less(&arr[index], &arr[0]) {
+                // Move wall to make more space, and bring element to left of wall.
+                wall += 1;
+                self.swap(arr, index, wall);
             }
+        }
+
+        // Bring pivot to its rightful position.
+        self.swap(arr, 0, wall);
+        wall
+    }
 
-            if arr[0] < arr[middle_index] && arr[middle_index] < *arr.last().unwrap() {
-                // Middle element is the median
-                return middle_index;
-            } else if arr[middle_index] < arr[0] && arr[0] < *arr.last().unwrap() {
-                // First element is the median
-                return 0;
+    // Partitions around the pivot at the first place into smaller, equal and larger elements.
+    // Returns the range of the equal elements.
+    fn partition_three_way<T>(&mut self, arr: &mut [T]) -> (usize, usize)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        // arr[..lower] is smaller than the pivot, arr[lower..index] equal, and arr[upper..] larger.
+        // arr[lower] is always equal to the pivot, so it serves as the pivot itself.
+        let mut lower = 0;
+        let mut index = 1;
+        let mut upper = arr.len();
+
+        while index < upper {
+            if self.less(&arr[index], &arr[lower]) {
+                self.swap(arr, lower, index);
+                lower += 1;
+                index += 1;
+            } else if self.less(&arr[lower], &arr[index]) {
+                upper -= 1;
+                self.swap(arr, index, upper);
             } else {
-                // Last element is the median
-                return arr.len() - 1;
+                index += 1;
+            }
+        }
+
+        (lower, upper)
+    }
+
+    // Get the pivot index based on the type we need.
+    fn get_pivot_index<T>(&mut self, arr: &[T]) -> usize
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        let last = arr.len() - 1;
+        match self.options.pivot_type {
+            PivotType::First => 0,
+            PivotType::Last => last,
+            // Course convention, the middle of an even length 2k array is its kth element.
+            PivotType::MedianOfThree => self.median_of_three(arr, 0, last / 2, last),
+            PivotType::Random => self.rng.gen_range(0..arr.len()),
+            PivotType::NintherTukey => {
+                // Not enough elements for three groups.
+                if arr.len() < 9 {
+                    return self.median_of_three(arr, 0, last / 2, last);
+                }
+
+                let position = |i: usize| i * last / 8;
+                let first = self.median_of_three(arr, position(0), position(1), position(2));
+                let second = self.median_of_three(arr, position(3), position(4), position(5));
+                let third = self.median_of_three(arr, position(6), position(7), position(8));
+                self.median_of_three(arr, first, second, third)
+            }
+        }
+    }
+
+    // Returns the index holding the median of the three elements.
+    fn median_of_three<T>(&mut self, arr: &[T], a: usize, b: usize, c: usize) -> usize
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        let a_less_b = self.less(&arr[a], &arr[b]);
+        let b_less_c = self.less(&arr[b], &arr[c]);
+
+        // b is between a and c.
+        if a_less_b == b_less_c {
+            return b;
+        }
+
+        // b is the largest or the smallest, so the median is the other end that is closer to b.
+        let a_less_c = self.less(&arr[a], &arr[c]);
+        if a_less_b == a_less_c {
+            c
+        } else {
+            a
+        }
+    }
+
+    // Compares two elements, and counts the comparison.
+    fn less<T>(&mut self, a: &T, b: &T) -> bool
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        if self.options.counting == CountingMode::Actual {
+            self.stats.comparisons += 1;
+        }
+        (self.compare)(a, b) == Ordering::Less
+    }
+
+    // Swaps two elements, and counts the swap.
+    fn swap<T>(&mut self, arr: &mut [T], i: usize, j: usize) {
+        if i != j {
+            arr.swap(i, j);
+            self.stats.swaps += 1;
+        }
+    }
+}
+
+// Runs every partition mode on inputs that are known to be hard for quick sort, and checks the results.
+fn check_adversarial_inputs(rng: &mut Rng) {
+    let length: i32 = 10000;
+    let inputs = [
+        ("all equal", vec![7; length as usize]),
+        ("sorted", (0..length).collect::<Vec<i32>>()),
+        ("reverse sorted", (0..length).rev().collect()),
+        ("organ pipe", (0..length / 2).chain((0..length / 2).rev()).collect()),
+    ];
+
+    // Introsort never goes deeper than 2.log n before switching to heap sort.
+    let depth_limit = 2 * (usize::BITS - (length as usize).leading_zeros()) as usize;
+
+    for (name, input) in inputs.iter() {
+        println!("\n{} ({} elements):", name, input.len());
+        for (partition, introsort) in [
+            (Partition::Wall, false),
+            (Partition::ThreeWay, false),
+            (Partition::Wall, true),
+            (Partition::ThreeWay, true),
+        ] {
+            let options = SortOptions {
+                partition,
+                introsort,
+                ..SortOptions::new(PivotType::First)
+            };
+
+            let mut sorted_array = input.clone();
+            let stats = quick_sort(&mut sorted_array, &options, rng);
+
+            let mut expected = input.clone();
+            expected.sort_unstable();
+            assert_eq!(sorted_array, expected, "{} input not sorted with {:?}", name, options);
+
+            if introsort {
+                assert!(
+                    stats.max_depth <= depth_limit,
+                    "Introsort went too deep on {} input",
+                    name
+                );
             }
+
+            // Every element is equal to the first pivot, so a single pass is enough.
+            if partition == Partition::ThreeWay && *name == "all equal" {
+                assert_eq!(stats.comparisons as usize, 2 * (input.len() - 1));
+                assert_eq!(stats.max_depth, 1);
+            }
+
+            println!(
+                "  {:?}{}: {:?}",
+                partition,
+                if introsort { " + introsort" } else { "" },
+                stats
+            );
         }
     }
 }
 
 fn main() {
-    let length: usize = 10000;
-    let mut input_array: Vec<i32> = Vec::with_capacity(length);
-
-    // Load dataset.
-    for _ in 0..length {
-        let mut input_buffer: String = String::new();
-        stdin()
-            .read_line(&mut input_buffer)
-            .expect("Failed to read value");
-
-        // Parse into to integer.
-        input_array.push(
-            input_buffer
-                .trim()
-                .parse()
-                .expect("Failed to convert to int"),
+    // Ensure proper usage.
+    let mut args = env::args().skip(1).collect::<Vec<String>>();
+    let mut rng = match Rng::from_args(&mut args) {
+        Some(rng) if args.is_empty() => rng,
+        _ => {
+            eprintln!("usage: ./quick_sort [--seed <seed>] < <input file>");
+            process::exit(1);
+        }
+    };
+
+    // Load dataset, one integer per line.
+    let input_array: Vec<i32> = stdin()
+        .lock()
+        .lines()
+        .map(|line| line.expect("Failed to read value"))
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.trim().parse().expect("Failed to convert to int"))
+        .collect();
+
+    // Comparisons as counted in the course assignment.
+    for pivot_type in [PivotType::First, PivotType::Last, PivotType::MedianOfThree] {
+        let options = SortOptions {
+            counting: CountingMode::Course,
+            ..SortOptions::new(pivot_type)
+        };
+        let stats = quick_sort(&mut input_array.clone(), &options, &mut rng);
+        println!("{:?}: Course comparisons = {}", pivot_type, stats.comparisons);
+    }
+
+    // Actual work done by every pivot type.
+    println!("\nRandom pivots seed = {}", rng.seed());
+    for pivot_type in [
+        PivotType::First,
+        PivotType::Last,
+        PivotType::MedianOfThree,
+        PivotType::Random,
+        PivotType::NintherTukey,
+    ] {
+        let mut sorted_array = input_array.clone();
+        let stats = quick_sort(&mut sorted_array, &SortOptions::new(pivot_type), &mut rng);
+        assert!(
+            sorted_array.windows(2).all(|pair| pair[0] <= pair[1]),
+            "Array is not sorted"
         );
+
+        println!("{:?}: {:?}", pivot_type, stats);
     }
 
-    // Sort the array.
-    let comparisons1: i32 = quick_sort(&mut input_array.clone(), &PivotType::First);
-    let comparisons2: i32 = quick_sort(&mut input_array.clone(), &PivotType::Last);
-    let comparisons3: i32 = quick_sort(&mut input_array.clone(), &PivotType::MedianOfThree);
-
-    // Print the sorted result.
-    println!("Sorting done");
-    println!(
-        "Comparisons1 = {}, Comparisons1 = {}, Comparisons1 = {}",
-        comparisons1, comparisons2, comparisons3
-    );
+    check_adversarial_inputs(&mut rng);
+
+    // Any type can be sorted with a comparator, here descending by length.
+    let mut words = vec!["quick", "sort", "is", "divide", "and", "conquer"];
+    quick_sort_by(&mut words, &SortOptions::new(PivotType::Random), &mut rng, |a, b| {
+        b.len().cmp(&a.len())
+    });
+    println!("\n{:?}", words);
 }
@@ -0,0 +1,120 @@
+/**
+ * Seedable pseudo-random number generator, xoshiro256**.
+ *
+ * Pure Rust, so it builds on any target, and the same seed always gives the same sequence, which makes randomized
+ * algorithms reproducible. The 64 bit seed is expanded into the 256 bit state with splitmix64, as the authors of
+ * xoshiro recommend. Not suitable for cryptography.
+ */
+use std::ops::Range;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub struct Rng {
+    seed: u64,
+    state: [u64; 4],
+}
+
+impl Rng {
+    // Generator for the given seed.
+    pub fn new(seed: u64) -> Rng {
+        // Splitmix64 never gives an all zero state, which xoshiro can't leave.
+        let mut splitmix = seed;
+        let mut state = [0; 4];
+        for word in state.iter_mut() {
+            splitmix = splitmix.wrapping_add(0x9E37_79B9_7F4A_7C15);
+            let mut z = splitmix;
+            z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+            *word = z ^ (z >> 31);
+        }
+
+        Rng { seed, state }
+    }
+
+    // Generator seeded with the current time.
+    pub fn from_time() -> Rng {
+        Rng::new(SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_nanos() as u64)
+    }
+
+    // Removes "--seed <seed>" from the arguments, and returns a generator with that seed, or seeded with the current
+    // time if there is no such flag. Returns None if the seed is missing or not a number.
+    pub fn from_args(args: &mut Vec<String>) -> Option<Rng> {
+        match args.iter().position(|arg| arg == "--seed") {
+            Some(index) => {
+                let seed = args.get(index + 1)?.parse::<u64>().ok()?;
+                args.drain(index..index + 2);
+                Some(Rng::new(seed))
+            }
+            None => Some(Rng::from_time()),
+        }
+    }
+
+    // Seed this generator started from, to run it again.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    // Next 64 random bits.
+    pub fn next_u64(&mut self) -> u64 {
+        let result = self.state[1].wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+        let t = self.state[1] << 17;
+
+        self.state[2] ^= self.state[0];
+        self.state[3] ^= self.state[1];
+        self.state[1] ^= self.state[2];
+        self.state[0] ^= self.state[3];
+        self.state[2] ^= t;
+        self.state[3] = self.state[3].rotate_left(45);
+
+        result
+    }
+
+    // Uniformly random number in range, which must not be empty.
+    pub fn gen_range(&mut self, range: Range<usize>) -> usize {
+        assert!(range.start < range.end, "Empty range {:?}", range);
+        let span = (range.end - range.start) as u64;
+
+        // Lemire's multiply and shift, rejecting the few values that would make lower results more likely.
+        let threshold = span.wrapping_neg() % span;
+        loop {
+            let product = self.next_u64() as u128 * span as u128;
+            if product as u64 >= threshold {
+                return range.start + (product >> 64) as usize;
+            }
+        }
+    }
+
+    // Shuffles the array in place, every order being equally likely (Fisher-Yates).
+    pub fn shuffle<T>(&mut self, array: &mut [T]) {
+        for i in (1..array.len()).rev() {
+            let j = self.gen_range(0..i + 1);
+            array.swap(i, j);
+        }
+    }
+
+    // New generator seeded from this one, for work that runs on another thread.
+    pub fn fork(&mut self) -> Rng {
+        Rng::new(self.next_u64())
+    }
+}
+
+fn main() {
+    // Same seed, same sequence.
+    let mut first = Rng::new(42);
+    let mut second = Rng::new(42);
+    let sequence = (0..5).map(|_| first.next_u64()).collect::<Vec<u64>>();
+    assert!(sequence.iter().all(|&value| value == second.next_u64()));
+    println!("Seed 42: {:?}", sequence);
+
+    // Every value of a small range comes up about as often.
+    let mut rng = Rng::from_time();
+    let mut counts = [0; 6];
+    for _ in 0..600_000 {
+        counts[rng.gen_range(0..6)] += 1;
+    }
+    assert!(counts.iter().all(|&count| (90_000..110_000).contains(&count)));
+    println!("Seed {}, rolls of a die: {:?}", rng.seed(), counts);
+
+    let mut cards = (1..=10).collect::<Vec<i32>>();
+    rng.shuffle(&mut cards);
+    println!("Shuffled: {:?}", cards);
+}
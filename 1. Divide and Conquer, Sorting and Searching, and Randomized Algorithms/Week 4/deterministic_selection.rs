@@ -1,86 +1,164 @@
 /**
  * Selection problem solved using median-of-medians technique.
  *
- * Find ith order statistics in an array of distinct numbers in O(n) time.
+ * Find ith order statistics in an array in O(n) worst case time, duplicates included. The selection itself lives in
+ * order_statistics.rs: it works in place on groups of 5, swaps their medians to the front of the array, and never
+ * takes more comparisons than median_of_medians_comparison_bound, which stays below 30n.
+ *
+ * Running this file checks the bound on adversarial inputs.
  */
-use std::convert::TryInto;
-
-// Return ith order statistics, or None.
-fn deterministic_select(arr: &mut [i32], ith_order: i32) -> Option<i32> {
-    // ith order statistics can't be bigger than the array.
-    if ith_order > arr.len().try_into().unwrap() || ith_order < 0 {
-        return None;
-    }
-
-    // Desired element not found.
-    if arr.is_empty() {
-        return None;
-    }
-
-    if arr.len() == 1 {
-        return Some(arr[0]);
-    }
-
-    // Apply median-of-medians trick. Divide array into chunks of size 5.
-    let mut chunks: Vec<Vec<i32>> = arr.chunks_mut(5).map(|chunk| chunk.to_vec()).collect();
-
-    // Sort partitions.
-    for chunk in chunks.iter_mut() {
-        chunk.sort_unstable();
-    }
-
-    // Get medians from sorted chunks.
-    let mut medians: Vec<i32> = chunks.iter().map(|chunk| get_median(&chunk)).collect();
-
-    // Find median of medians.
-    let final_median_index = (medians.len() / 2) as i32;
-    let final_median = deterministic_select(&mut medians, final_median_index).unwrap();
-
-    // Get index of median. We will consider this as pivot index.
-    let mut pivot_index: usize = 0;
-    for item in arr.iter().enumerate() {
-        if *item.1 == final_median {
-            pivot_index = item.0;
-            break;
-        }
-    }
-
-    // Use Quick sort partition routine to find the ith minimum.
-    arr.swap(0, pivot_index);
-    let mut wall = 0;
+use std::env;
+use std::process;
+
+#[allow(dead_code)]
+mod order_statistics;
+use order_statistics::rng::Rng;
+use order_statistics::{median_of_medians_comparison_bound, select_nth_with_stats, SelectStats, Strategy};
+
+// Return ith order statistics (0 is the minimum), or None.
+fn deterministic_select(arr: &mut [i32], ith_order: usize) -> Option<i32> {
+    // Median of medians never draws a random number.
+    select_nth_with_stats(arr, ith_order, Strategy::MedianOfMedians, &mut Rng::new(0))
+        .0
+        .copied()
+}
 
-    for index in 1..arr.len() {
-        if arr[index] < arr[0] {
-            wall += 1;
-            arr.swap(wall, index);
+// Inputs that are hard on pivot rules, with the given length.
+fn adversarial_inputs(length: usize, rng: &mut Rng) -> Vec<(&'static str, Vec<i32>)> {
+    let n = length as i32;
+    let mut inputs = vec![
+        ("sorted", (0..n).collect()),
+        ("reversed", (0..n).rev().collect()),
+        ("all equal", vec![7; length]),
+        ("two values", (0..n).map(|i| i % 2).collect()),
+        ("organ pipe", (0..n).map(|i| i.min(n - 1 - i)).collect()),
+        ("sawtooth", (0..n).map(|i| i % 5).rev().collect()),
+    ];
+
+    // Musser's median of 3 killer, for even lengths.
+    if length.is_multiple_of(2) {
+        let half = n / 2;
+        let mut killer = vec![0; length];
+        for i in 1..=half {
+            if i % 2 == 1 {
+                killer[(i - 1) as usize] = i;
+                killer[i as usize] = half + i;
+            }
+            killer[(half + i - 1) as usize] = 2 * i;
         }
+        inputs.push(("median of 3 killer", killer));
     }
 
-    arr.swap(0, wall);
+    inputs.push((
+        "random",
+        (0..length).map(|_| rng.gen_range(0..length + 1) as i32).collect(),
+    ));
 
-    if ith_order == wall.try_into().unwrap() {
-        return Some(arr[wall]);
-    } else if ith_order < wall.try_into().unwrap() {
-        return deterministic_select(&mut arr[..wall], ith_order);
-    } else {
-        return deterministic_select(&mut arr[wall + 1..], ith_order - (wall as i32) - 1);
-    }
+    inputs
 }
 
-// Get median from sorted array.
-fn get_median(arr: &[i32]) -> i32 {
-    if arr.len() % 2 != 0 {
-        return arr[arr.len() / 2];
-    } else {
-        return arr[(arr.len() + 1) / 2];
+// Selects the smallest, median and largest element of every adversarial input with every strategy, checking results
+// against sorting and comparison counts against the worst case bound. Returns the most comparisons per strategy.
+fn check_adversarial_inputs(length: usize, print: bool, rng: &mut Rng) -> [SelectStats; 3] {
+    let strategies = [Strategy::MedianOfMedians, Strategy::Introselect, Strategy::Random];
+    let bound = median_of_medians_comparison_bound(length);
+    assert!(
+        bound <= 30 * length as u64,
+        "Bound is not linear for {} elements",
+        length
+    );
+
+    // Introselect gives up on cheap pivots after two partitions that don't halve the array, so it costs at most
+    // 2 * (n + 2) for every halving, the last insertion sort, and a median of medians selection.
+    let log_length = (usize::BITS - length.leading_zeros()) as u64;
+    let introselect_bound = 4 * length as u64 + 4 * (log_length + 1) + 10 + bound;
+
+    let mut worst = [SelectStats::default(); 3];
+    for (name, input) in adversarial_inputs(length, rng) {
+        let mut sorted = input.clone();
+        sorted.sort_unstable();
+
+        for (index, &strategy) in strategies.iter().enumerate() {
+            for &k in [0, length / 2, length - 1].iter() {
+                let mut array = input.clone();
+                let (value, stats) = select_nth_with_stats(&mut array, k, strategy, rng);
+                assert_eq!(value, Some(&sorted[k]), "{:?} failed on {} input", strategy, name);
+
+                match strategy {
+                    Strategy::MedianOfMedians => assert!(
+                        stats.comparisons <= bound,
+                        "{} comparisons on {} input of {} elements, over the bound {}",
+                        stats.comparisons,
+                        name,
+                        length,
+                        bound
+                    ),
+                    Strategy::Introselect => assert!(
+                        stats.comparisons <= introselect_bound,
+                        "{} introselect comparisons on {} input of {} elements, over the bound {}",
+                        stats.comparisons,
+                        name,
+                        length,
+                        introselect_bound
+                    ),
+                    Strategy::Random => {}
+                }
+
+                worst[index].comparisons = worst[index].comparisons.max(stats.comparisons);
+                worst[index].swaps = worst[index].swaps.max(stats.swaps);
+            }
+
+            if print && strategy == Strategy::MedianOfMedians {
+                let (_, stats) = select_nth_with_stats(&mut input.clone(), length / 2, strategy, rng);
+                println!(
+                    "{:<20} median: {:>9} comparisons ({:.2}n), {:>9} swaps",
+                    name,
+                    stats.comparisons,
+                    stats.comparisons as f64 / length as f64,
+                    stats.swaps
+                );
+            }
+        }
     }
+
+    worst
 }
 
 fn main() {
+    // Ensure proper usage.
+    let mut args = env::args().skip(1).collect::<Vec<String>>();
+    let mut rng = match Rng::from_args(&mut args) {
+        Some(rng) if args.is_empty() => rng,
+        _ => {
+            eprintln!("usage: ./deterministic_selection [--seed <seed>]");
+            process::exit(1);
+        }
+    };
+
     let mut data1 = [10, 9, 8, 7, 6, 5, 4, 3, 2, 1, 12, 11, 14, 13];
     let ith_order_statistics = 5;
 
     let result = deterministic_select(&mut data1, ith_order_statistics - 1);
 
     println!("{:?}", result);
+
+    // Every short length, covering all the ways groups of 5 can be cut.
+    for length in 1..=200 {
+        check_adversarial_inputs(length, false, &mut rng);
+    }
+
+    println!("\nSeed = {}", rng.seed());
+    for &length in [1_000, 100_000, 1_000_000].iter() {
+        println!(
+            "\n{} elements, median of medians bound {} comparisons ({:.2}n)",
+            length,
+            median_of_medians_comparison_bound(length),
+            median_of_medians_comparison_bound(length) as f64 / length as f64
+        );
+        let worst = check_adversarial_inputs(length, length == 1_000_000, &mut rng);
+        println!(
+            "Most comparisons: median of medians {}, introselect {}, random {}",
+            worst[0].comparisons, worst[1].comparisons, worst[2].comparisons
+        );
+    }
 }
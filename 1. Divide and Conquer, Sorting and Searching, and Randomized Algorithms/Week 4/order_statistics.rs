@@ -0,0 +1,407 @@
+/**
+ * Order statistics.
+ * Find the kth smallest element (k = 0 is the minimum) of any ordered type in O(n) time.
+ *
+ * Duplicates follow the standard rank semantics, the kth order statistic is the element that would be at index k once
+ * the array is sorted. Partitions are three-way, so runs of duplicates are settled in a single step.
+ *
+ * Strategies to choose the pivot:
+ * - Random: random pivot, O(n) expected time.
+ * - MedianOfMedians: median of the medians of groups of 5, O(n) worst case time.
+ * - Introselect: median of three pivots, switching to median of medians once two partitions in a row fail to halve
+ *   the array. O(n) worst case, and about as fast as random pivots on typical inputs.
+ *
+ * Median of medians works in place without allocating. Every group of 5 is sorted by insertion sort, and its median
+ * is swapped to the front of the array, where the median of the medians is selected recursively.
+ *
+ * Worst case comparisons, counting one per three-way comparison:
+ * - Sorting the groups takes at most 10 comparisons per 5 elements, 2n overall.
+ * - Partitioning compares every other element with the pivot once, n - 1.
+ * - Recursing on the g = ceil(n / 5) medians takes C(g).
+ * - At least (g - 1) / 2 full groups have a median no smaller than the pivot, and as many have a median no larger.
+ *   Each of them puts 3 elements on that side of the pivot, one more comes from the pivot's own group, and elements
+ *   equal to the pivot drop out. The search goes on with at most n - 3 * ((g - 1) / 2) - 1 <= 7n / 10 + 2 elements.
+ *
+ * So C(n) <= 3n + C(n / 5 + 1) + C(7n / 10 + 2), which is linear since 1 / 5 + 7 / 10 < 1 and tends to 30n.
+ * median_of_medians_comparison_bound evaluates this recurrence exactly.
+ */
+use std::cmp::{max, min, Ordering};
+use std::env;
+use std::process;
+
+#[allow(dead_code)]
+#[path = "../Week 3/rng.rs"]
+pub mod rng;
+use self::rng::Rng;
+
+// How the pivot of every partition is chosen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Strategy {
+    Random,
+    MedianOfMedians,
+    Introselect,
+}
+
+// Arrays this small are sorted directly.
+const SMALL_LENGTH: usize = 5;
+
+// Work done by a selection.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SelectStats {
+    pub comparisons: u64,
+    pub swaps: u64,
+}
+
+// Returns the kth smallest element, or None if k is out of range. Random pivots are drawn from rng.
+// Array is reordered so that index k holds that element, with smaller elements before it and larger ones after.
+pub fn select_nth<'a, T: Ord>(array: &'a mut [T], k: usize, strategy: Strategy, rng: &mut Rng) -> Option<&'a T> {
+    select_nth_with_stats(array, k, strategy, rng).0
+}
+
+// Same as select_nth, but also returns the number of comparisons and swaps it took.
+pub fn select_nth_with_stats<'a, T: Ord>(
+    array: &'a mut [T],
+    k: usize,
+    strategy: Strategy,
+    rng: &mut Rng,
+) -> (Option<&'a T>, SelectStats) {
+    if k >= array.len() {
+        return (None, SelectStats::default());
+    }
+
+    let mut selector = Selector::new(strategy, rng);
+    let progress = Progress::new(array.len(), strategy);
+    selector.select(array, k, progress);
+
+    (Some(&array[k]), selector.stats)
+}
+
+// Returns the elements of every rank in [ranks] in the same order, or None if any rank is out of range.
+// All ranks are found in a single recursive pass, which is cheaper than selecting them one by one.
+pub fn select_many<'a, T: Ord>(
+    array: &'a mut [T],
+    ranks: &[usize],
+    strategy: Strategy,
+    rng: &mut Rng,
+) -> Option<Vec<&'a T>> {
+    if ranks.iter().any(|&rank| rank >= array.len()) {
+        return None;
+    }
+
+    // Every rank only needs to be found once.
+    let mut sorted_ranks = ranks.to_vec();
+    sorted_ranks.sort_unstable();
+    sorted_ranks.dedup();
+
+    let mut selector = Selector::new(strategy, rng);
+    let progress = Progress::new(array.len(), strategy);
+    selector.select_many(array, &mut sorted_ranks, progress);
+
+    let array: &'a [T] = array;
+    Some(ranks.iter().map(|&rank| &array[rank]).collect())
+}
+
+// Most comparisons select_nth can take with the MedianOfMedians strategy on an array of [length] elements.
+// Evaluates the recurrence from the header for every length up to [length], since smaller arrays may cost more.
+pub fn median_of_medians_comparison_bound(length: usize) -> u64 {
+    // Insertion sort of short arrays compares every pair at most once.
+    let mut bounds = (0..=min(length, SMALL_LENGTH) as u64)
+        .map(|n| n * n.saturating_sub(1) / 2)
+        .collect::<Vec<u64>>();
+
+    for n in SMALL_LENGTH + 1..=length {
+        let groups = n.div_ceil(5);
+        let last_group = (n % 5) as u64;
+        let group_sorting = 10 * (n / 5) as u64 + last_group * last_group.saturating_sub(1) / 2;
+        let remaining = n - 3 * ((groups - 1) / 2) - 1;
+
+        let bound = group_sorting + bounds[groups] + (n - 1) as u64 + bounds[remaining];
+        bounds.push(max(bounds[n - 1], bound));
+    }
+
+    bounds[length]
+}
+
+// Tracks whether introselect is still making progress with cheap pivots.
+#[derive(Debug, Clone, Copy)]
+struct Progress {
+    median_of_medians: bool,
+    introselect: bool,
+    // Array length two partitions ago.
+    checkpoint: usize,
+    steps: usize,
+}
+
+impl Progress {
+    fn new(length: usize, strategy: Strategy) -> Progress {
+        Progress {
+            median_of_medians: strategy == Strategy::MedianOfMedians,
+            introselect: strategy == Strategy::Introselect,
+            checkpoint: length,
+            steps: 0,
+        }
+    }
+
+    // Records a partition that left [length] elements to search.
+    fn record(&mut self, length: usize) {
+        if !self.introselect || self.median_of_medians {
+            return;
+        }
+
+        // Every two partitions have to at least halve the array.
+        self.steps += 1;
+        if self.steps == 2 {
+            if length > self.checkpoint / 2 {
+                self.median_of_medians = true;
+            }
+            self.checkpoint = length;
+            self.steps = 0;
+        }
+    }
+}
+
+// State shared by the recursive calls of a single selection.
+struct Selector<'a> {
+    strategy: Strategy,
+    stats: SelectStats,
+    rng: &'a mut Rng,
+}
+
+impl<'a> Selector<'a> {
+    fn new(strategy: Strategy, rng: &'a mut Rng) -> Selector<'a> {
+        Selector {
+            strategy,
+            stats: SelectStats::default(),
+            rng,
+        }
+    }
+
+    // Moves the kth smallest element to index k.
+    fn select<T: Ord>(&mut self, mut array: &mut [T], mut k: usize, mut progress: Progress) {
+        loop {
+            if array.len() <= SMALL_LENGTH {
+                self.insertion_sort(array);
+                return;
+            }
+
+            // Partition around the pivot, and keep searching only on the side that holds k.
+            let (lower, upper) = self.partition_around_pivot(array, &progress);
+            if k < lower {
+                array = &mut std::mem::take(&mut array)[..lower];
+            } else if k >= upper {
+                array = &mut std::mem::take(&mut array)[upper..];
+                k -= upper;
+            } else {
+                // k falls among the elements equal to the pivot.
+                return;
+            }
+
+            progress.record(array.len());
+        }
+    }
+
+    // Moves the element of every rank to its index. Ranks must be sorted and unique.
+    fn select_many<T: Ord>(&mut self, array: &mut [T], ranks: &mut [usize], mut progress: Progress) {
+        match ranks.len() {
+            0 => return,
+            1 => return self.select(array, ranks[0], progress),
+            _ => {}
+        }
+
+        if array.len() <= SMALL_LENGTH {
+            self.insertion_sort(array);
+            return;
+        }
+
+        let (lower, upper) = self.partition_around_pivot(array, &progress);
+
+        // Ranks below the equal elements go left, ranks above go right, and ranks in between are already in place.
+        let left_count = ranks.iter().take_while(|&&rank| rank < lower).count();
+        let right_start = ranks.iter().take_while(|&&rank| rank < upper).count();
+        let (left_ranks, rest) = ranks.split_at_mut(left_count);
+        let right_ranks = &mut rest[right_start - left_count..];
+        for rank in right_ranks.iter_mut() {
+            *rank -= upper;
+        }
+
+        let (left, right) = array.split_at_mut(upper);
+        let left = &mut left[..lower];
+
+        let mut left_progress = progress;
+        left_progress.record(left.len());
+        self.select_many(left, left_ranks, left_progress);
+
+        progress.record(right.len());
+        self.select_many(right, right_ranks, progress);
+    }
+
+    // Chooses a pivot and partitions around it. Returns the range of elements equal to the pivot.
+    fn partition_around_pivot<T: Ord>(&mut self, array: &mut [T], progress: &Progress) -> (usize, usize) {
+        let last = array.len() - 1;
+        let pivot_index = if progress.median_of_medians {
+            self.median_of_medians(array)
+        } else {
+            match self.strategy {
+                Strategy::Random => self.rng.gen_range(0..array.len()),
+                _ => self.median_of_three(array, 0, last / 2, last),
+            }
+        };
+
+        self.swap(array, 0, pivot_index);
+        self.partition_three_way(array)
+    }
+
+    // Returns the index of the median of the medians of groups of 5 elements.
+    fn median_of_medians<T: Ord>(&mut self, array: &mut [T]) -> usize {
+        // Sort every group, and swap its median to the front of the array.
+        let groups = array.len().div_ceil(5);
+        for group in 0..groups {
+            let start = group * 5;
+            let end = min(start + 5, array.len());
+            self.insertion_sort(&mut array[start..end]);
+            self.swap(array, group, start + (end - start - 1) / 2);
+        }
+
+        // Median of the medians, found recursively.
+        let middle = (groups - 1) / 2;
+        let progress = Progress::new(groups, Strategy::MedianOfMedians);
+        self.select(&mut array[..groups], middle, progress);
+
+        middle
+    }
+
+    // Partitions around the pivot at the first place into smaller, equal and larger elements.
+    // Returns the range of the equal elements.
+    fn partition_three_way<T: Ord>(&mut self, array: &mut [T]) -> (usize, usize) {
+        // array[..lower] is smaller than the pivot, array[lower..index] equal, and array[upper..] larger.
+        // array[lower] is always equal to the pivot, so it serves as the pivot itself.
+        let mut lower = 0;
+        let mut index = 1;
+        let mut upper = array.len();
+
+        while index < upper {
+            match self.compare(&array[index], &array[lower]) {
+                Ordering::Less => {
+                    self.swap(array, lower, index);
+                    lower += 1;
+                    index += 1;
+                }
+                Ordering::Greater => {
+                    upper -= 1;
+                    self.swap(array, index, upper);
+                }
+                Ordering::Equal => index += 1,
+            }
+        }
+
+        (lower, upper)
+    }
+
+    // Returns the index holding the median of the three elements.
+    fn median_of_three<T: Ord>(&mut self, array: &[T], a: usize, b: usize, c: usize) -> usize {
+        let a_less_b = self.compare(&array[a], &array[b]) == Ordering::Less;
+        let b_less_c = self.compare(&array[b], &array[c]) == Ordering::Less;
+
+        // b is between a and c.
+        if a_less_b == b_less_c {
+            return b;
+        }
+
+        // b is the largest or the smallest, so the median is the other end that is closer to b.
+        if a_less_b == (self.compare(&array[a], &array[c]) == Ordering::Less) {
+            c
+        } else {
+            a
+        }
+    }
+
+    // Sorts a short array.
+    fn insertion_sort<T: Ord>(&mut self, array: &mut [T]) {
+        for i in 1..array.len() {
+            let mut j = i;
+            while j > 0 && self.compare(&array[j], &array[j - 1]) == Ordering::Less {
+                self.swap(array, j, j - 1);
+                j -= 1;
+            }
+        }
+    }
+
+    // Compares two elements, and counts the comparison.
+    fn compare<T: Ord>(&mut self, a: &T, b: &T) -> Ordering {
+        self.stats.comparisons += 1;
+        a.cmp(b)
+    }
+
+    // Swaps two elements, and counts the swap.
+    fn swap<T>(&mut self, array: &mut [T], a: usize, b: usize) {
+        self.stats.swaps += 1;
+        array.swap(a, b);
+    }
+}
+
+fn main() {
+    // Ensure proper usage.
+    let mut args = env::args().skip(1).collect::<Vec<String>>();
+    let mut rng = match Rng::from_args(&mut args) {
+        Some(rng) if args.is_empty() => rng,
+        _ => {
+            eprintln!("usage: ./order_statistics [--seed <seed>]");
+            process::exit(1);
+        }
+    };
+    println!("Seed = {}", rng.seed());
+
+    let strategies = [Strategy::Random, Strategy::MedianOfMedians, Strategy::Introselect];
+
+    // Test cases, with duplicates.
+    let data = [
+        vec![2, 1, 3, 6, 5, 8, 0],
+        vec![2, 2, 3, 4, 8, 2],
+        vec![2],
+        vec![2, 2, 2, 2, 2, 2, 2],
+    ];
+    for array in data.iter() {
+        for k in 0..=array.len() {
+            let results = strategies
+                .iter()
+                .map(|&strategy| select_nth(&mut array.clone(), k, strategy, &mut rng).copied())
+                .collect::<Vec<Option<i32>>>();
+            println!("{:?}, k = {}: {:?}", array, k, results);
+        }
+    }
+
+    // Several order statistics at once.
+    let mut words = vec!["median", "of", "medians", "is", "linear", "in", "the", "worst", "case"];
+    println!(
+        "\n{:?}",
+        select_many(&mut words, &[0, 4, 8], Strategy::Introselect, &mut rng)
+    );
+
+    // Check every strategy against sorting on random arrays full of duplicates.
+    for round in 0..200 {
+        let length = 1 + round * 37;
+        let array = (0..length)
+            .map(|_| rng.gen_range(0..1 + length / 4))
+            .collect::<Vec<usize>>();
+
+        let mut sorted = array.clone();
+        sorted.sort_unstable();
+        let ranks = [0, length / 3, length / 2, length - 1];
+
+        for &strategy in strategies.iter() {
+            for &rank in ranks.iter() {
+                assert_eq!(
+                    select_nth(&mut array.clone(), rank, strategy, &mut rng),
+                    Some(&sorted[rank])
+                );
+            }
+
+            let expected = ranks.iter().map(|&rank| &sorted[rank]).collect::<Vec<&usize>>();
+            assert_eq!(
+                select_many(&mut array.clone(), &ranks, strategy, &mut rng),
+                Some(expected)
+            );
+        }
+    }
+    println!("All strategies agree with sorting");
+}
@@ -4,103 +4,487 @@
  *
  * Logic: Keep removing random edges until two vertices are left.
  * We may not get the correct results first time, hence run it multiple times to compute the lowest value.
+ *
+ * Karger-Stein only contracts down to n / √2 vertices, then branches into two independent contractions of what is
+ * left, recursively. Early contractions rarely destroy the min cut, so both branches share them, and a single run
+ * finds the min cut with probability Ω(1 / log n) in O(n² log n) time, instead of Ω(1 / n²) for plain contraction.
+ * Trials are repeated, in parallel, until the chance of missing the min cut is below a target.
  */
+use std::cmp::min;
 use std::collections::HashMap;
-use std::io::Error;
-use std::{env, fs};
-
-fn compute_min_cut(graph: &mut HashMap<i32, Vec<i32>>) -> usize {
-    // Base case: Can't divide graph further.
-    if graph.len() == 2 {
-        let key = graph.keys().next().unwrap();
-        return graph.get(key).unwrap().len();
-    }
-
-    // Get a random vertex from graph.
-    let keys = graph.keys().map(|val| *val).collect::<Vec<i32>>();
-    let vertex_i = keys[random() % keys.len()];
-
-    // Get random vertex on the other end of edge.
-    let edges = graph.get(&vertex_i).unwrap().clone();
-    let vertex_j = edges[random() % edges.len()];
-
-    // Prepare to remove vertex_i, point all edges connected with vertex_i to vertex_j.
-    for vertex in edges {
-        // Remove edge between vertex_i and this vertex.
-        let index = graph
-            .get(&vertex)
-            .unwrap()
-            .iter()
-            .position(|&item| item == vertex_i)
-            .unwrap();
-        graph.get_mut(&vertex).unwrap().remove(index);
-
-        // Create an edge between this vertex to vertex_j. Check added to prevent self loops.
-        if vertex != vertex_j {
-            graph.get_mut(&vertex).unwrap().push(vertex_j);
-            graph.get_mut(&vertex_j).unwrap().push(vertex);
-        }
+use std::f64::consts::SQRT_2;
+use std::path::Path;
+use std::thread;
+use std::{env, fs, process};
+
+#[allow(dead_code)]
+#[path = "../Week 3/rng.rs"]
+pub mod rng;
+use self::rng::Rng;
+
+#[allow(dead_code)]
+#[path = "../../3. Greedy Algorithms, Minimum Spanning Trees, and Dynamic Programming/Week 2/union_find.rs"]
+mod union_find;
+use self::union_find::UnionFind;
+
+// Graphs this small are cut by trying every partition.
+const BRUTE_FORCE_VERTICES: usize = 6;
+
+// Known min cut of the course's input_graph.txt.
+const INPUT_GRAPH_MIN_CUT: usize = 17;
+
+// Undirected multigraph, as a list of edges between vertex indices.
+#[derive(Debug, Clone)]
+pub struct Graph {
+    // Label of every vertex, as written in the input.
+    pub labels: Vec<i32>,
+    pub edges: Vec<(usize, usize)>,
+}
+
+// Undirected graph with weighted edges between vertex indices.
+#[derive(Debug, Clone)]
+pub struct WeightedGraph {
+    // Label of every vertex, as written in the input.
+    pub labels: Vec<i32>,
+    pub edges: Vec<(usize, usize, u64)>,
+}
+
+// A cut of a graph, its two sides and the edges crossing between them.
+#[derive(Debug, Clone)]
+pub struct Cut {
+    pub side: Vec<i32>,
+    pub other_side: Vec<i32>,
+    // Crossing edges, from side to other side.
+    pub crossing_edges: Vec<(i32, i32)>,
+}
+
+// A cut of a weighted graph, its two sides and the edges crossing between them.
+#[derive(Debug, Clone)]
+pub struct WeightedCut {
+    pub side: Vec<i32>,
+    pub other_side: Vec<i32>,
+    // Crossing edges with their weights, from side to other side.
+    pub crossing_edges: Vec<(i32, i32, u64)>,
+}
+
+impl Cut {
+    // Number of crossing edges.
+    pub fn size(&self) -> usize {
+        self.crossing_edges.len()
+    }
+}
+
+impl WeightedCut {
+    // Total weight of the crossing edges.
+    pub fn weight(&self) -> u64 {
+        self.crossing_edges.iter().map(|&(_, _, weight)| weight).sum()
+    }
+}
+
+impl Graph {
+    // Parses an adjacency list as a weighted graph, where an edge of weight w becomes w parallel edges.
+    pub fn parse(contents: &str) -> Result<Graph, String> {
+        WeightedGraph::parse(contents).map(|graph| graph.to_multigraph())
     }
 
-    // Remove vertex_i.
-    graph.remove(&vertex_i);
+    pub fn vertex_count(&self) -> usize {
+        self.labels.len()
+    }
 
-    // Compute min cut for remaining graph.
-    return compute_min_cut(graph);
+    // Cut between the vertices on side true and the ones on side false.
+    pub fn cut(&self, sides: &[bool]) -> Cut {
+        let cut = weighted_cut(&self.labels, self.edges.iter().map(|&(u, v)| (u, v, 1)), sides);
+        Cut {
+            side: cut.side,
+            other_side: cut.other_side,
+            crossing_edges: cut.crossing_edges.iter().map(|&(u, v, _)| (u, v)).collect(),
+        }
+    }
 }
 
-fn main() -> Result<(), Error> {
-    // Ensure proper usage.
-    let args = env::args().skip(1).collect::<Vec<String>>();
-    if args.len() != 1 {
-        eprintln!("usage: <filename>");
-        std::process::exit(1);
+impl WeightedGraph {
+    // Parses an adjacency list, one vertex per line followed by its neighbours, either "vertex" for weight 1 or
+    // "vertex,weight". Every edge is listed at both of its ends, and parallel edges are listed once per copy.
+    pub fn parse(contents: &str) -> Result<WeightedGraph, String> {
+        let mut rows = Vec::new();
+        for (index, line) in contents.lines().enumerate() {
+            let mut items = line.split_whitespace();
+            let vertex = match items.next() {
+                Some(item) => item
+                    .parse::<i32>()
+                    .map_err(|error| format!("{}: {}", index + 1, error))?,
+                None => continue,
+            };
+
+            let mut neighbours = Vec::new();
+            for item in items {
+                let mut parts = item.split(',');
+                let neighbour = parts.next().unwrap_or_default().parse::<i32>();
+                let weight = parts.next().map_or(Ok(1), |weight| weight.parse::<u64>());
+                match (neighbour, weight, parts.next()) {
+                    (Ok(neighbour), Ok(weight), None) => neighbours.push((neighbour, weight)),
+                    _ => {
+                        return Err(format!(
+                            "{}: '{}' is not a vertex or a vertex,weight pair",
+                            index + 1,
+                            item
+                        ))
+                    }
+                }
+            }
+            rows.push((index + 1, vertex, neighbours));
+        }
+
+        // Index of every vertex.
+        let mut indices = HashMap::new();
+        for &(line_number, vertex, _) in rows.iter() {
+            if indices.insert(vertex, indices.len()).is_some() {
+                return Err(format!("{}: vertex {} is listed twice", line_number, vertex));
+            }
+        }
+
+        // Keep every edge at its smaller end only, and drop self loops.
+        let mut edges = Vec::new();
+        for (line_number, vertex, neighbours) in rows.iter() {
+            for &(neighbour, weight) in neighbours.iter() {
+                match indices.get(&neighbour) {
+                    Some(&index) if *vertex < neighbour => edges.push((indices[vertex], index, weight)),
+                    Some(_) => {}
+                    None => return Err(format!("{}: unknown vertex {}", line_number, neighbour)),
+                }
+            }
+        }
+
+        Ok(WeightedGraph {
+            labels: rows.iter().map(|&(_, vertex, _)| vertex).collect(),
+            edges,
+        })
     }
 
-    // Try to open the file
-    let filename = &args[0];
-    let contents = fs::read_to_string(filename)?;
+    // Same graph with every weight w turned into w parallel edges, for random contraction.
+    pub fn to_multigraph(&self) -> Graph {
+        Graph {
+            labels: self.labels.clone(),
+            edges: self
+                .edges
+                .iter()
+                .flat_map(|&(u, v, weight)| (0..weight).map(move |_| (u, v)))
+                .collect(),
+        }
+    }
 
-    // Create an adjacency list.
-    let mut adjacency_list: HashMap<i32, Vec<i32>> = HashMap::new();
+    pub fn vertex_count(&self) -> usize {
+        self.labels.len()
+    }
+
+    // Cut between the vertices on side true and the ones on side false.
+    pub fn cut(&self, sides: &[bool]) -> WeightedCut {
+        weighted_cut(&self.labels, self.edges.iter().copied(), sides)
+    }
+}
 
-    // Loop over to contents of file to build our graph.
-    for row in contents.split_terminator(['\n']) {
-        let row_line = row
-            .split_whitespace()
-            .map(|item| item.parse::<i32>().unwrap())
+// Cut between the vertices on side true and the ones on side false, given the vertex labels and weighted edges.
+fn weighted_cut(labels: &[i32], edges: impl Iterator<Item = (usize, usize, u64)>, sides: &[bool]) -> WeightedCut {
+    let label_side = |side: bool| {
+        let mut side_labels = (0..labels.len())
+            .filter(|&vertex| sides[vertex] == side)
+            .map(|vertex| labels[vertex])
             .collect::<Vec<i32>>();
+        side_labels.sort_unstable();
+        side_labels
+    };
 
-        // First number is the vertex, rest are the edges of that vertex.
-        adjacency_list.insert(row_line[0], row_line[1..].to_vec());
+    let mut crossing_edges = edges
+        .filter(|&(u, v, _)| sides[u] != sides[v])
+        .map(|(u, v, weight)| match sides[u] {
+            true => (labels[u], labels[v], weight),
+            false => (labels[v], labels[u], weight),
+        })
+        .collect::<Vec<(i32, i32, u64)>>();
+    crossing_edges.sort_unstable();
+
+    WeightedCut {
+        side: label_side(true),
+        other_side: label_side(false),
+        crossing_edges,
     }
+}
 
-    let max_iterations = 500;
+// Randomly contracts edges until [target] super vertices are left, or no edge is left to contract.
+// Edges are contracted in a random order, which is the same as picking a uniformly random remaining edge every time.
+// Returns the super vertex of every vertex, numbered from 0, and the number of super vertices.
+fn contract(vertex_count: usize, edges: &mut [(usize, usize)], target: usize, rng: &mut Rng) -> (Vec<usize>, usize) {
+    rng.shuffle(edges);
 
-    let mut min_value = 10000000;
-    for index in 0..max_iterations {
-        let min_cuts = compute_min_cut(&mut adjacency_list.clone());
-        println!("Iteration {} = {} min cuts", index, min_cuts);
+    let mut union_find = UnionFind::new();
+    for vertex in 0..vertex_count {
+        union_find.add(vertex as i32);
+    }
 
-        if min_cuts < min_value {
-            min_value = min_cuts;
+    let mut count = vertex_count;
+    for &(u, v) in edges.iter() {
+        if count <= target {
+            break;
         }
+
+        // Edge is a self loop by now.
+        if union_find.find(u as i32) == union_find.find(v as i32) {
+            continue;
+        }
+        union_find.union(u as i32, v as i32);
+        count -= 1;
     }
 
-    println!("\nMin Cuts = {}", min_value);
+    // Number the super vertices in order of their first vertex.
+    let mut numbers = HashMap::new();
+    let super_vertices = (0..vertex_count)
+        .map(|vertex| {
+            let next = numbers.len();
+            *numbers.entry(union_find.find(vertex as i32)).or_insert(next)
+        })
+        .collect();
 
-    Ok(())
+    (super_vertices, count)
+}
+
+// Returns the edges between different super vertices.
+fn contracted_edges(edges: &[(usize, usize)], super_vertices: &[usize]) -> Vec<(usize, usize)> {
+    edges
+        .iter()
+        .map(|&(u, v)| (super_vertices[u], super_vertices[v]))
+        .filter(|&(u, v)| u != v)
+        .collect()
+}
+
+// Plain random contraction down to two vertices. Finds a min cut with probability at least 2 / (n(n - 1)).
+pub fn karger(graph: &Graph, rng: &mut Rng) -> Cut {
+    let (super_vertices, _) = contract(graph.vertex_count(), &mut graph.edges.clone(), 2, rng);
+    let sides = super_vertices
+        .iter()
+        .map(|&super_vertex| super_vertex == 0)
+        .collect::<Vec<bool>>();
+    graph.cut(&sides)
+}
+
+// Number of vertices Karger-Stein contracts down to before branching. Contraction keeps a given min cut with
+// probability at least t(t - 1) / (n(n - 1)), which is 1 / 2 at this size.
+fn contraction_target(vertex_count: usize) -> usize {
+    (1.0 + vertex_count as f64 / SQRT_2).ceil() as usize
+}
+
+// One run of Karger-Stein on a connected multigraph. Returns the cut size and the side of every vertex.
+fn recursive_contraction(vertex_count: usize, edges: &[(usize, usize)], rng: &mut Rng) -> (usize, Vec<bool>) {
+    if vertex_count <= BRUTE_FORCE_VERTICES {
+        return brute_force_min_cut(vertex_count, edges);
+    }
+
+    // Contract twice independently, and keep the better cut.
+    let target = contraction_target(vertex_count);
+    let mut best: Option<(usize, Vec<bool>)> = None;
+    for _ in 0..2 {
+        let (super_vertices, count) = contract(vertex_count, &mut edges.to_vec(), target, rng);
+        let (size, super_sides) = recursive_contraction(count, &contracted_edges(edges, &super_vertices), rng);
+
+        if best.as_ref().is_none_or(|(best_size, _)| size < *best_size) {
+            let sides = super_vertices
+                .iter()
+                .map(|&super_vertex| super_sides[super_vertex])
+                .collect();
+            best = Some((size, sides));
+        }
+    }
+
+    best.unwrap()
+}
+
+// Exact min cut of a small multigraph, trying every way to split its vertices in two.
+fn brute_force_min_cut(vertex_count: usize, edges: &[(usize, usize)]) -> (usize, Vec<bool>) {
+    // Last vertex always stays on side false, so every split is tried once.
+    let mut best = (usize::MAX, 0);
+    for mask in 1..1usize << (vertex_count - 1) {
+        let size = edges
+            .iter()
+            .filter(|&&(u, v)| (mask >> u & 1) != (mask >> v & 1))
+            .count();
+        if size < best.0 {
+            best = (size, mask);
+        }
+    }
+
+    let sides = (0..vertex_count).map(|vertex| best.1 >> vertex & 1 == 1).collect();
+    (best.0, sides)
+}
+
+// Lower bound on the probability that a single Karger-Stein run finds a min cut of a graph with [vertex_count]
+// vertices. Each branch keeps the min cut through its contraction, then finds it recursively.
+pub fn karger_stein_success_probability(vertex_count: usize) -> f64 {
+    if vertex_count <= BRUTE_FORCE_VERTICES {
+        return 1.0;
+    }
+
+    let n = vertex_count as f64;
+    let target = contraction_target(vertex_count);
+    let survival = (target * (target - 1)) as f64 / (n * (n - 1.0));
+    let branch = survival * karger_stein_success_probability(target);
+
+    1.0 - (1.0 - branch) * (1.0 - branch)
+}
+
+// Number of Karger-Stein runs needed to find a min cut with at least [success_probability].
+pub fn karger_stein_trials(vertex_count: usize, success_probability: f64) -> usize {
+    let single = karger_stein_success_probability(vertex_count);
+    if single >= success_probability {
+        return 1;
+    }
+
+    ((1.0 - success_probability).ln() / (1.0 - single).ln()).ceil() as usize
+}
+
+// Min cut of the graph, found with at least [success_probability] by Karger-Stein runs spread over all cores.
+// Returns None if the graph has fewer than two vertices.
+pub fn karger_stein(graph: &Graph, success_probability: f64, rng: &mut Rng) -> Option<Cut> {
+    let vertex_count = graph.vertex_count();
+    if vertex_count < 2 {
+        return None;
+    }
+
+    // Disconnected graph, any connected component is a cut with no crossing edges.
+    let (super_vertices, count) = contract(vertex_count, &mut graph.edges.clone(), 1, rng);
+    if count > 1 {
+        let sides = super_vertices
+            .iter()
+            .map(|&super_vertex| super_vertex == 0)
+            .collect::<Vec<bool>>();
+        return Some(graph.cut(&sides));
+    }
+
+    // Every thread gets its own generator and an even share of the trials.
+    let trials = karger_stein_trials(vertex_count, success_probability);
+    let threads = min(
+        trials,
+        thread::available_parallelism().map_or(1, |threads| threads.get()),
+    );
+    let rngs = (0..threads).map(|_| rng.fork()).collect::<Vec<Rng>>();
+
+    let results = thread::scope(|scope| {
+        let handles = rngs
+            .into_iter()
+            .enumerate()
+            .map(|(index, mut thread_rng)| {
+                let thread_trials = trials / threads + usize::from(index < trials % threads);
+                scope.spawn(move || {
+                    (0..thread_trials)
+                        .map(|_| recursive_contraction(vertex_count, &graph.edges, &mut thread_rng))
+                        .min_by_key(|(size, _)| *size)
+                })
+            })
+            .collect::<Vec<_>>();
+
+        handles
+            .into_iter()
+            .filter_map(|handle| handle.join().unwrap())
+            .collect::<Vec<_>>()
+    });
+
+    let (_, sides) = results.into_iter().min_by_key(|(size, _)| *size)?;
+    Some(graph.cut(&sides))
 }
 
-fn random() -> usize {
-    unsafe {
-        srand(time(0));
-        rand()
+// Checks Karger-Stein against brute force on random small multigraphs.
+fn check(rng: &mut Rng) {
+    // Karger-Stein finds the min cut of every graph with at least this probability, so brute force should always agree.
+    let success_probability = 1.0 - 1e-9;
+
+    for round in 0..300 {
+        let vertex_count = 2 + round % 11;
+        let mut graph = Graph {
+            labels: (1..=vertex_count as i32).collect(),
+            edges: Vec::new(),
+        };
+        for u in 0..vertex_count {
+            for v in u + 1..vertex_count {
+                for _ in 0..rng.gen_range(0..3) {
+                    graph.edges.push((u, v));
+                }
+            }
+        }
+
+        let cut = karger_stein(&graph, success_probability, rng).unwrap();
+        assert_eq!(cut.side.len() + cut.other_side.len(), vertex_count);
+        assert!(
+            !cut.side.is_empty() && !cut.other_side.is_empty(),
+            "One side of the cut is empty"
+        );
+        let (size, _) = brute_force_min_cut(vertex_count, &graph.edges);
+        assert_eq!(cut.size(), size, "Karger-Stein differs from brute force");
     }
+
+    println!("\nChecked against brute force on 300 random multigraphs");
 }
 
-extern "C" {
-    fn time(value: u32) -> u32;
-    fn srand(seed: u32);
-    fn rand() -> usize;
+fn main() -> Result<(), String> {
+    let usage = "usage: <filename> [--success-probability <probability>] [--seed <seed>]";
+
+    // Ensure proper usage.
+    let mut args = env::args().skip(1).collect::<Vec<String>>();
+    let mut rng = Rng::from_args(&mut args).unwrap_or_else(|| {
+        eprintln!("{}", usage);
+        process::exit(1);
+    });
+
+    let mut success_probability = 0.99;
+    if let Some(index) = args.iter().position(|arg| arg == "--success-probability") {
+        match args.get(index + 1).and_then(|arg| arg.parse::<f64>().ok()) {
+            Some(probability) if probability > 0.0 && probability < 1.0 => success_probability = probability,
+            _ => {
+                eprintln!("{}", usage);
+                process::exit(1);
+            }
+        }
+        args.drain(index..index + 2);
+    }
+
+    if args.len() != 1 {
+        eprintln!("{}", usage);
+        process::exit(1);
+    }
+
+    // Try to open the file
+    let filename = &args[0];
+    let contents = fs::read_to_string(filename).map_err(|error| format!("{}: {}", filename, error))?;
+    let graph = Graph::parse(&contents).map_err(|error| format!("{}:{}", filename, error))?;
+    println!("Seed = {}", rng.seed());
+
+    // Plain contraction, many times over.
+    let max_iterations = 500;
+    let min_value = (0..max_iterations)
+        .map(|_| karger(&graph, &mut rng).size())
+        .min()
+        .unwrap_or(0);
+    println!("Karger, best of {} runs: Min Cuts = {}", max_iterations, min_value);
+
+    // Karger-Stein, with just enough runs.
+    let trials = karger_stein_trials(graph.vertex_count(), success_probability);
+    match karger_stein(&graph, success_probability, &mut rng) {
+        Some(cut) => {
+            println!(
+                "Karger-Stein, {} runs for a {} success probability: Min Cuts = {}",
+                trials,
+                success_probability,
+                cut.size()
+            );
+            println!("\nSide ({} vertices): {:?}", cut.side.len(), cut.side);
+            println!("\nOther side ({} vertices): {:?}", cut.other_side.len(), cut.other_side);
+            println!("\nCrossing edges: {:?}", cut.crossing_edges);
+
+            if Path::new(filename).ends_with("input_graph.txt") {
+                assert_eq!(cut.size(), INPUT_GRAPH_MIN_CUT, "Karger-Stein missed the known min cut");
+            }
+        }
+        None => println!("Graph has fewer than two vertices, there is no cut"),
+    }
+
+    check(&mut rng);
+
+    Ok(())
 }
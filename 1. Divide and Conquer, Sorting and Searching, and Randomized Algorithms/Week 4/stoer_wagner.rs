@@ -0,0 +1,186 @@
+/**
+ * Stoer-Wagner global minimum cut, for weighted undirected graphs.
+ *
+ * Deterministic, unlike random contraction. Every phase adds vertices one at a time, always the one most tightly
+ * connected to those already added. The cut between the last vertex and all the others is a minimum cut between
+ * the last two vertices, which are then merged. The lightest of these n - 1 phase cuts is the global min cut.
+ * Runs in O(n³) time with an adjacency matrix.
+ *
+ * Reads the same adjacency lists as random contraction, where every neighbour is an edge of weight 1, or
+ * "vertex,weight" pairs as in the Dijkstra input.
+ */
+use std::{env, fs, process};
+
+#[allow(dead_code)]
+mod random_contraction_undirected_graph;
+use random_contraction_undirected_graph::rng::Rng;
+use random_contraction_undirected_graph::{karger_stein, WeightedCut, WeightedGraph};
+
+// Global min cut of the graph, or None if it has fewer than two vertices.
+pub fn stoer_wagner(graph: &WeightedGraph) -> Option<WeightedCut> {
+    let vertex_count = graph.vertex_count();
+    if vertex_count < 2 {
+        return None;
+    }
+
+    // Weight between every pair of (merged) vertices, parallel edges add up.
+    let mut weights = vec![vec![0u64; vertex_count]; vertex_count];
+    for &(u, v, weight) in graph.edges.iter() {
+        weights[u][v] += weight;
+        weights[v][u] += weight;
+    }
+
+    // Original vertices merged into every vertex, and the vertices not merged away yet.
+    let mut members = (0..vertex_count)
+        .map(|vertex| vec![vertex])
+        .collect::<Vec<Vec<usize>>>();
+    let mut active = (0..vertex_count).collect::<Vec<usize>>();
+    let mut best: Option<(u64, Vec<usize>)> = None;
+
+    while active.len() > 1 {
+        // Add the most tightly connected vertex at every step.
+        let mut connection = vec![0u64; vertex_count];
+        let mut added = vec![false; vertex_count];
+        let (mut previous, mut last) = (active[0], active[0]);
+        for _ in 0..active.len() {
+            let next = *active
+                .iter()
+                .filter(|&&vertex| !added[vertex])
+                .max_by_key(|&&vertex| connection[vertex])
+                .unwrap();
+
+            added[next] = true;
+            previous = last;
+            last = next;
+            for &vertex in active.iter() {
+                connection[vertex] += weights[next][vertex];
+            }
+        }
+
+        // Cut of the phase, the last vertex against everything else.
+        let cut_weight = connection[last];
+        if best.as_ref().is_none_or(|(best_weight, _)| cut_weight < *best_weight) {
+            best = Some((cut_weight, members[last].clone()));
+        }
+
+        // Merge the last two vertices.
+        let last_members = std::mem::take(&mut members[last]);
+        members[previous].extend(last_members);
+        for &vertex in active.iter() {
+            weights[previous][vertex] += weights[last][vertex];
+            weights[vertex][previous] = weights[previous][vertex];
+        }
+        weights[previous][previous] = 0;
+        active.retain(|&vertex| vertex != last);
+    }
+
+    let (_, side) = best?;
+    let mut sides = vec![false; vertex_count];
+    for vertex in side {
+        sides[vertex] = true;
+    }
+    Some(graph.cut(&sides))
+}
+
+// Exact min cut weight of a small graph, trying every way to split its vertices in two.
+fn brute_force_min_cut(graph: &WeightedGraph) -> u64 {
+    let vertex_count = graph.vertex_count();
+    (1..1usize << (vertex_count - 1))
+        .map(|mask| {
+            graph
+                .edges
+                .iter()
+                .filter(|&&(u, v, _)| (mask >> u & 1) != (mask >> v & 1))
+                .map(|&(_, _, weight)| weight)
+                .sum::<u64>()
+        })
+        .min()
+        .unwrap()
+}
+
+// Checks Stoer-Wagner against brute force and Karger-Stein on random small weighted graphs.
+fn cross_check(rng: &mut Rng) {
+    // High enough that a Karger-Stein cut heavier than the Stoer-Wagner one points at a bug, not bad luck.
+    let success_probability = 1.0 - 1e-9;
+
+    for round in 0..300 {
+        let vertex_count = 2 + round % 11;
+        let mut graph = WeightedGraph {
+            labels: (1..=vertex_count as i32).collect(),
+            edges: Vec::new(),
+        };
+        for u in 0..vertex_count {
+            for v in u + 1..vertex_count {
+                if rng.gen_range(0..10) < 4 {
+                    graph.edges.push((u, v, rng.gen_range(1..5) as u64));
+                }
+            }
+        }
+
+        let cut = stoer_wagner(&graph).unwrap();
+        assert_eq!(cut.side.len() + cut.other_side.len(), vertex_count);
+        assert!(
+            !cut.side.is_empty() && !cut.other_side.is_empty(),
+            "One side of the cut is empty"
+        );
+        assert_eq!(
+            cut.weight(),
+            brute_force_min_cut(&graph),
+            "Stoer-Wagner differs from brute force"
+        );
+
+        let karger_cut = karger_stein(&graph.to_multigraph(), success_probability, rng).unwrap();
+        assert_eq!(
+            cut.weight(),
+            karger_cut.size() as u64,
+            "Stoer-Wagner differs from Karger-Stein"
+        );
+    }
+
+    println!("Cross-checked against brute force and Karger-Stein on 300 random weighted graphs");
+}
+
+fn main() -> Result<(), String> {
+    // Ensure proper usage.
+    let mut args = env::args().skip(1).collect::<Vec<String>>();
+    let mut rng = match Rng::from_args(&mut args) {
+        Some(rng) if args.len() == 1 => rng,
+        _ => {
+            eprintln!("usage: ./stoer_wagner <filename> [--seed <seed>]");
+            process::exit(1);
+        }
+    };
+
+    let filename = &args[0];
+    let contents = fs::read_to_string(filename).map_err(|error| format!("{}: {}", filename, error))?;
+    let graph = WeightedGraph::parse(&contents).map_err(|error| format!("{}:{}", filename, error))?;
+    println!("Seed = {}", rng.seed());
+
+    let cut = match stoer_wagner(&graph) {
+        Some(cut) => cut,
+        None => {
+            println!("Graph has fewer than two vertices, there is no cut");
+            return Ok(());
+        }
+    };
+
+    println!("Stoer-Wagner: Min Cut weight = {}", cut.weight());
+    println!("\nSide ({} vertices): {:?}", cut.side.len(), cut.side);
+    println!("\nOther side ({} vertices): {:?}", cut.other_side.len(), cut.other_side);
+    println!("\nCrossing edges: {:?}", cut.crossing_edges);
+
+    // Karger-Stein finds some cut, which can never be lighter than the min cut. Heavy weights would turn into too
+    // many parallel edges, so only unweighted graphs are compared.
+    if graph.edges.iter().all(|&(_, _, weight)| weight == 1) {
+        let karger_cut = karger_stein(&graph.to_multigraph(), 0.99, &mut rng).unwrap();
+        assert!(
+            cut.weight() <= karger_cut.size() as u64,
+            "Karger-Stein found a lighter cut"
+        );
+        println!("\nKarger-Stein: Min Cuts = {}", karger_cut.size());
+    }
+
+    cross_check(&mut rng);
+
+    Ok(())
+}
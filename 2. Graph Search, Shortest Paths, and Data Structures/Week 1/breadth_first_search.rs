@@ -1,8 +1,33 @@
 /**
  * Breadth first search.
  * Compute shortest path in an undirected graph in O(m + n) time.
+ *
+ * Records the vertex every vertex was discovered from, which makes a BFS tree of shortest paths. Unreachable
+ * vertices have no distance. Searching from several sources gives the distance to the nearest one, and a search for
+ * one target stops as soon as it is discovered.
+ *
+ * Bidirectional search finds a path between two vertices by growing BFS levels from both ends, always the smaller
+ * frontier, until they meet. In graphs where the number of vertices within d hops grows fast, each side only
+ * explores about half the depth, a tiny fraction of what one full search explores. Directed graphs are searched
+ * backwards from the target along the reversed adjacency list.
+ *
+ * Undirected graphs store every edge in both directions. Their structure queries:
+ * - connected_components: component of every vertex, by BFS.
+ * - is_bipartite: a two-colouring by BFS levels, or an odd cycle through an edge inside a level.
+ * - find_cycle: some cycle, from the first DFS edge back to a vertex still on the stack.
+ * - articulation_points and bridges: DFS low-links, the earliest discovered vertex every subtree reaches back to.
+ *   A vertex is an articulation point if a child subtree can't reach above it (the root if it has two children),
+ *   and a tree edge is a bridge if the subtree below it can't reach above the edge. Parallel edges are not bridges.
  */
-use std::collections::VecDeque;
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, VecDeque};
+use std::time::Instant;
+use std::{env, process};
+
+#[allow(dead_code)]
+#[path = "../../1. Divide and Conquer, Sorting and Searching, and Randomized Algorithms/Week 3/rng.rs"]
+mod rng;
+use rng::Rng;
 
 #[derive(Debug)]
 // Graph representation in adjacency list.
@@ -11,6 +36,41 @@ struct Graph {
     vertices: usize,
     // Adjacency list from vertex (0..n).
     adj_list: Vec<Vec<usize>>,
+    // Reversed adjacency list of same graph (used to search backwards from a target)
+    rev_adj_list: Vec<Vec<usize>>,
+}
+
+// Articulation points and bridges of an undirected graph.
+#[derive(Debug)]
+struct CutStructure {
+    articulation_points: Vec<usize>,
+    // Edges with the smaller vertex first, sorted.
+    bridges: Vec<(usize, usize)>,
+}
+
+// Shortest paths found by a breadth first search.
+#[derive(Debug)]
+struct BfsTree {
+    // Distance from the nearest source, None if the vertex was not reached.
+    distances: Vec<Option<usize>>,
+    // Vertex every vertex was discovered from, None for sources and vertices that were not reached.
+    parents: Vec<Option<usize>>,
+    // Number of vertices whose edges were explored.
+    expanded: usize,
+}
+
+impl BfsTree {
+    // Returns a shortest path from a source to [vertex], or None if it was not reached.
+    fn path_to(&self, vertex: usize) -> Option<Vec<usize>> {
+        self.distances[vertex]?;
+
+        let mut path = vec![vertex];
+        while let Some(parent) = self.parents[path[path.len() - 1]] {
+            path.push(parent);
+        }
+        path.reverse();
+        Some(path)
+    }
 }
 
 impl Graph {
@@ -19,51 +79,573 @@ impl Graph {
         Graph {
             vertices,
             adj_list: vec![Vec::<usize>::new(); vertices],
+            rev_adj_list: vec![Vec::<usize>::new(); vertices],
         }
     }
 
-    // Creates an edge from v1 to v2.
+    // Creates an edge from v1 to v2, and reverse edge from v2 to v1
     fn add_edge(&mut self, v1: usize, v2: usize) {
         self.adj_list[v1].push(v2);
+        self.rev_adj_list[v2].push(v1);
+    }
+
+    // Creates an undirected edge between v1 and v2.
+    fn add_undirected_edge(&mut self, v1: usize, v2: usize) {
+        self.add_edge(v1, v2);
+        if v1 != v2 {
+            self.add_edge(v2, v1);
+        }
     }
 
     // Returns shortest path to all vertices starting from [start_vertex].
-    fn shortest_path(&self, start_vertex: usize) -> Vec<usize> {
-        // Maintain distance of every vertex.
-        let mut dist = vec![0; self.vertices];
-        dist[start_vertex] = 0;
+    fn shortest_path(&self, start_vertex: usize) -> BfsTree {
+        self.breadth_first_search(&[start_vertex], None)
+    }
 
-        // Mark start_vertex as explored.
-        let mut explored = vec![false; self.vertices];
-        explored[start_vertex] = true;
+    // Searches from all [sources] at once, and stops as soon as [target] is reached, if there is one.
+    fn breadth_first_search(&self, sources: &[usize], target: Option<usize>) -> BfsTree {
+        // Maintain distance and parent of every vertex, None until it is explored.
+        let mut tree = BfsTree {
+            distances: vec![None; self.vertices],
+            parents: vec![None; self.vertices],
+            expanded: 0,
+        };
 
         let mut queue = VecDeque::new();
-        queue.push_back(start_vertex);
-
-        // Start exploring, starting from start_vertex.
-        while !queue.is_empty() {
-            // Get the topmost element from queue.
-            if let Some(top_vertex) = queue.pop_front() {
-                // Explore all neighbouring vertices from this vertex.
-                for v in self.adj_list.get(top_vertex).unwrap() {
-                    // This is not yet explored
-                    if !explored[*v] {
-                        explored[*v] = true;
-                        dist[*v] = dist[top_vertex] + 1;
-
-                        // Mark this vertex explore its neighbours.
-                        queue.push_back(*v);
+        for &source in sources {
+            if tree.distances[source].is_none() {
+                tree.distances[source] = Some(0);
+                queue.push_back(source);
+            }
+        }
+        if target.is_some_and(|target| tree.distances[target].is_some()) {
+            return tree;
+        }
+
+        // Start exploring, starting from the sources.
+        while let Some(top_vertex) = queue.pop_front() {
+            let distance = tree.distances[top_vertex].unwrap();
+            tree.expanded += 1;
+
+            // Explore all neighbouring vertices from this vertex.
+            for &v in self.adj_list[top_vertex].iter() {
+                // This is not yet explored
+                if tree.distances[v].is_none() {
+                    tree.distances[v] = Some(distance + 1);
+                    tree.parents[v] = Some(top_vertex);
+
+                    // Its distance is final as soon as it is discovered.
+                    if target == Some(v) {
+                        return tree;
+                    }
+
+                    // Mark this vertex explore its neighbours.
+                    queue.push_back(v);
+                }
+            }
+        }
+
+        tree
+    }
+
+    // Returns a shortest path from [source] to [target], or None if there is none, and the number of vertices whose
+    // edges were explored.
+    fn bidirectional_search(&self, source: usize, target: usize) -> (Option<Vec<usize>>, usize) {
+        if source == target {
+            return (Some(vec![source]), 0);
+        }
+
+        // Distance and parent of every vertex reached from the source, and towards the target. Maps rather than
+        // arrays, so a query only costs the vertices it reaches.
+        let mut reached: [HashMap<usize, (usize, Option<usize>)>; 2] = [HashMap::new(), HashMap::new()];
+        reached[0].insert(source, (0, None));
+        reached[1].insert(target, (0, None));
+
+        let mut frontiers = [vec![source], vec![target]];
+        let mut expanded = 0;
+        // Shortest path through an edge between both searches, as (length, forward end, backward end).
+        let mut best: Option<(usize, usize, usize)> = None;
+
+        while best.is_none() && !frontiers[0].is_empty() && !frontiers[1].is_empty() {
+            // Grow the smaller frontier by one whole level.
+            let side = if frontiers[0].len() <= frontiers[1].len() { 0 } else { 1 };
+            let adj_list = if side == 0 { &self.adj_list } else { &self.rev_adj_list };
+            let mut next_frontier = Vec::new();
+
+            for &u in frontiers[side].iter() {
+                expanded += 1;
+                let distance = reached[side][&u].0;
+                for &v in adj_list[u].iter() {
+                    if let Entry::Vacant(entry) = reached[side].entry(v) {
+                        entry.insert((distance + 1, Some(u)));
+                        next_frontier.push(v);
+                    }
+
+                    // Edge u - v joins both searches. Finish the level, a later edge may still be shorter.
+                    if let Some(&(other_distance, _)) = reached[1 - side].get(&v) {
+                        let length = distance + 1 + other_distance;
+                        let ends = if side == 0 { (u, v) } else { (v, u) };
+                        if best.is_none_or(|(best_length, _, _)| length < best_length) {
+                            best = Some((length, ends.0, ends.1));
+                        }
+                    }
+                }
+            }
+
+            frontiers[side] = next_frontier;
+        }
+
+        let (_, forward_end, backward_end) = match best {
+            Some(best) => best,
+            None => return (None, expanded),
+        };
+
+        // Source to the forward end, then the backward end to the target.
+        let mut path = vec![forward_end];
+        while let Some(parent) = reached[0][&path[path.len() - 1]].1 {
+            path.push(parent);
+        }
+        path.reverse();
+        path.push(backward_end);
+        while let Some(parent) = reached[1][&path[path.len() - 1]].1 {
+            path.push(parent);
+        }
+
+        (Some(path), expanded)
+    }
+
+    // Returns the component of every vertex of an undirected graph, numbered from 0 in order of their lowest vertex.
+    // Every search starts from a vertex no earlier search reached, so each vertex and edge is explored once.
+    fn connected_components(&self) -> Vec<usize> {
+        let mut components = vec![None; self.vertices];
+        let mut count = 0;
+        let mut queue = VecDeque::new();
+
+        for vertex in 0..self.vertices {
+            if components[vertex].is_some() {
+                continue;
+            }
+
+            // Everything the search reaches is in this component, and was in no earlier one.
+            components[vertex] = Some(count);
+            queue.push_back(vertex);
+            while let Some(u) = queue.pop_front() {
+                for &v in self.adj_list[u].iter() {
+                    if components[v].is_none() {
+                        components[v] = Some(count);
+                        queue.push_back(v);
+                    }
+                }
+            }
+            count += 1;
+        }
+
+        components.into_iter().map(Option::unwrap).collect()
+    }
+
+    // Returns a colour for every vertex of an undirected graph so that every edge joins different colours, or the
+    // vertices of an odd cycle if there is no such colouring.
+    fn is_bipartite(&self) -> Result<Vec<bool>, Vec<usize>> {
+        let mut colours = vec![false; self.vertices];
+        let mut parents = vec![None; self.vertices];
+        let mut explored = vec![false; self.vertices];
+
+        for root in 0..self.vertices {
+            if explored[root] {
+                continue;
+            }
+            explored[root] = true;
+            let mut queue = VecDeque::new();
+            queue.push_back(root);
+
+            while let Some(u) = queue.pop_front() {
+                for &v in self.adj_list[u].iter() {
+                    if !explored[v] {
+                        explored[v] = true;
+                        colours[v] = !colours[u];
+                        parents[v] = Some(u);
+                        queue.push_back(v);
+                    } else if u == v {
+                        return Err(vec![u]);
+                    } else if colours[v] == colours[u] {
+                        // Same level parity, so the tree paths from both ends to where they join, plus this edge,
+                        // make an odd cycle.
+                        let ancestors = |mut vertex: usize| {
+                            let mut path = vec![vertex];
+                            while let Some(parent) = parents[vertex] {
+                                path.push(parent);
+                                vertex = parent;
+                            }
+                            path
+                        };
+                        let (mut from_u, mut from_v) = (ancestors(u), ancestors(v));
+                        while from_u.len() > 1 && from_u[from_u.len() - 2] == from_v[from_v.len() - 2] {
+                            from_u.pop();
+                            from_v.pop();
+                        }
+
+                        // u up to the common ancestor, then down to v.
+                        from_v.pop();
+                        from_u.extend(from_v.into_iter().rev());
+                        return Err(from_u);
+                    }
+                }
+            }
+        }
+
+        Ok(colours)
+    }
+
+    // Returns the vertices of some cycle of an undirected graph, in order, or None if it is a forest. A self loop is
+    // a cycle of one vertex, and parallel edges a cycle of two.
+    fn find_cycle(&self) -> Option<Vec<usize>> {
+        // Position of every vertex on the DFS stack, None once it is finished or before it is explored.
+        let mut on_stack = vec![None; self.vertices];
+        let mut explored = vec![false; self.vertices];
+
+        for root in 0..self.vertices {
+            if explored[root] {
+                continue;
+            }
+
+            // Vertex, its parent, next edge to explore, and whether the edge to its parent was skipped already.
+            let mut stack = vec![(root, None, 0, false)];
+            explored[root] = true;
+            on_stack[root] = Some(0);
+
+            while let Some(&mut (u, parent, ref mut next, ref mut skipped_parent)) = stack.last_mut() {
+                if *next == self.adj_list[u].len() {
+                    on_stack[u] = None;
+                    stack.pop();
+                    continue;
+                }
+
+                let v = self.adj_list[u][*next];
+                *next += 1;
+
+                if Some(v) == parent && !*skipped_parent {
+                    // Edge back to the parent, only a cycle if it is there twice.
+                    *skipped_parent = true;
+                } else if let Some(position) = on_stack[v] {
+                    // Back to a vertex on the stack, the cycle is the stack from there.
+                    return Some(stack[position..].iter().map(|&(vertex, _, _, _)| vertex).collect());
+                } else if !explored[v] {
+                    explored[v] = true;
+                    on_stack[v] = Some(stack.len());
+                    stack.push((v, Some(u), 0, false));
+                }
+            }
+        }
+
+        None
+    }
+
+    // Returns the articulation points of an undirected graph.
+    fn articulation_points(&self) -> Vec<usize> {
+        self.cut_structure().articulation_points
+    }
+
+    // Returns the bridges of an undirected graph.
+    fn bridges(&self) -> Vec<(usize, usize)> {
+        self.cut_structure().bridges
+    }
+
+    // Computes articulation points and bridges with one DFS.
+    fn cut_structure(&self) -> CutStructure {
+        // Discovery time of every vertex, and the earliest one its subtree has an edge to.
+        let mut discovered: Vec<Option<usize>> = vec![None; self.vertices];
+        let mut low = vec![0; self.vertices];
+        let mut time = 0;
+        let mut is_articulation = vec![false; self.vertices];
+        let mut bridges = Vec::new();
+
+        for root in 0..self.vertices {
+            if discovered[root].is_some() {
+                continue;
+            }
+            discovered[root] = Some(time);
+            low[root] = time;
+            time += 1;
+            let mut root_children = 0;
+
+            // Vertex, its parent, next edge to explore, and whether the edge to its parent was skipped already.
+            let mut stack = vec![(root, None, 0, false)];
+            while let Some(&mut (u, parent, ref mut next, ref mut skipped_parent)) = stack.last_mut() {
+                if *next < self.adj_list[u].len() {
+                    let v = self.adj_list[u][*next];
+                    *next += 1;
+
+                    if Some(v) == parent && !*skipped_parent {
+                        *skipped_parent = true;
+                    } else if let Some(time_v) = discovered[v] {
+                        low[u] = low[u].min(time_v);
+                    } else {
+                        discovered[v] = Some(time);
+                        low[v] = time;
+                        time += 1;
+                        stack.push((v, Some(u), 0, false));
+                    }
+                    continue;
+                }
+
+                // Done with u, pass its low-link up to the parent.
+                stack.pop();
+                if let Some(p) = parent {
+                    low[p] = low[p].min(low[u]);
+                    let time_p = discovered[p].unwrap();
+                    if low[u] > time_p {
+                        bridges.push((p.min(u), p.max(u)));
+                    }
+                    if p == root {
+                        root_children += 1;
+                    } else if low[u] >= time_p {
+                        is_articulation[p] = true;
                     }
                 }
             }
+
+            is_articulation[root] = root_children >= 2;
         }
 
-        return dist;
+        bridges.sort_unstable();
+        CutStructure {
+            articulation_points: (0..self.vertices).filter(|&v| is_articulation[v]).collect(),
+            bridges,
+        }
+    }
+}
+
+// Random directed graph where every vertex has 1 to 2 * degree - 1 edges, to random vertices.
+fn random_graph(vertices: usize, degree: usize, rng: &mut Rng) -> Graph {
+    let mut graph = Graph::new(vertices);
+    for v in 0..vertices {
+        for _ in 0..1 + rng.gen_range(0..2 * degree - 1) {
+            graph.add_edge(v, rng.gen_range(0..vertices));
+        }
+    }
+    graph
+}
+
+// Checks that a path follows edges of the graph, from source to target.
+fn check_path(graph: &Graph, path: &[usize], source: usize, target: usize) {
+    assert_eq!((path[0], path[path.len() - 1]), (source, target));
+    for edge in path.windows(2) {
+        assert!(
+            graph.adj_list[edge[0]].contains(&edge[1]),
+            "No edge {} -> {}",
+            edge[0],
+            edge[1]
+        );
+    }
+}
+
+// Checks bidirectional search against BFS distances on small random graphs, many pairs disconnected.
+fn cross_check(rng: &mut Rng) {
+    for round in 0..300 {
+        let vertices = 1 + round % 40;
+        let graph = random_graph(vertices, 1 + round % 3, rng);
+
+        for source in 0..vertices {
+            let tree = graph.shortest_path(source);
+            for target in 0..vertices {
+                let (path, _) = graph.bidirectional_search(source, target);
+                assert_eq!(path.as_ref().map(|path| path.len() - 1), tree.distances[target]);
+                if let Some(path) = path {
+                    check_path(&graph, &path, source, target);
+                }
+            }
+        }
+    }
+
+    println!("Cross-checked bidirectional search against BFS on 300 random directed graphs");
+}
+
+// Random undirected graph, with self loops and parallel edges.
+fn random_undirected_graph(vertices: usize, edges: usize, rng: &mut Rng) -> Graph {
+    let mut graph = Graph::new(vertices);
+    for _ in 0..edges {
+        graph.add_undirected_edge(rng.gen_range(0..vertices), rng.gen_range(0..vertices));
+    }
+    graph
+}
+
+// Number of connected components once a vertex, or one copy of an edge, is taken out.
+fn components_without(graph: &Graph, vertex: Option<usize>, edge: Option<(usize, usize)>) -> usize {
+    let mut copy = Graph::new(graph.vertices);
+    let mut removed = false;
+    for u in 0..graph.vertices {
+        for &v in graph.adj_list[u].iter() {
+            if u < v && !removed && edge == Some((u, v)) {
+                removed = true;
+            } else if u <= v && vertex != Some(u) && vertex != Some(v) {
+                copy.add_undirected_edge(u, v);
+            }
+        }
+    }
+
+    let components = copy.connected_components();
+    let mut labels = (0..graph.vertices)
+        .filter(|&v| vertex != Some(v))
+        .map(|v| components[v])
+        .collect::<Vec<usize>>();
+    labels.sort_unstable();
+    labels.dedup();
+    labels.len()
+}
+
+// Whether the vertices follow edges of the graph all the way round, without repeating a vertex.
+fn is_cycle(graph: &Graph, cycle: &[usize]) -> bool {
+    let mut sorted = cycle.to_vec();
+    sorted.sort_unstable();
+    sorted.dedup();
+
+    let edge_count = |u: usize, v: usize| graph.adj_list[u].iter().filter(|&&w| w == v).count();
+    let closing = match cycle.len() {
+        1 => edge_count(cycle[0], cycle[0]) >= 1,
+        2 => edge_count(cycle[0], cycle[1]) >= 2,
+        _ => edge_count(cycle[cycle.len() - 1], cycle[0]) >= 1,
+    };
+    sorted.len() == cycle.len() && closing && cycle.windows(2).all(|pair| edge_count(pair[0], pair[1]) >= 1)
+}
+
+// Checks the structure queries against their definitions on small random undirected graphs.
+fn check_structure(rng: &mut Rng) {
+    for round in 0..1000 {
+        let vertices = 1 + round % 13;
+        let graph = random_undirected_graph(vertices, rng.gen_range(0..2 * vertices), rng);
+        let edges = (0..vertices)
+            .flat_map(|u| graph.adj_list[u].iter().filter(move |&&v| u <= v).map(move |&v| (u, v)))
+            .collect::<Vec<(usize, usize)>>();
+
+        // Components hold both ends of every edge, numbered by their lowest vertex.
+        let components = graph.connected_components();
+        let count = components_without(&graph, None, None);
+        assert!(edges.iter().all(|&(u, v)| components[u] == components[v]));
+        assert_eq!(components.iter().max().map(|&max| max + 1), Some(count));
+        assert!((0..vertices).all(|v| components[v] == 0 || components[..v].contains(&(components[v] - 1))));
+
+        match graph.is_bipartite() {
+            Ok(colours) => assert!(edges.iter().all(|&(u, v)| colours[u] != colours[v])),
+            Err(cycle) => assert!(
+                cycle.len() % 2 == 1 && is_cycle(&graph, &cycle),
+                "Bad odd cycle {:?}",
+                cycle
+            ),
+        }
+
+        // Forests have one edge less than vertices in every component.
+        match graph.find_cycle() {
+            Some(cycle) => assert!(
+                is_cycle(&graph, &cycle),
+                "Bad cycle {:?} in {:?}",
+                cycle,
+                graph.adj_list
+            ),
+            None => assert_eq!(edges.len() + count, vertices, "Missed a cycle in {:?}", graph.adj_list),
+        }
+
+        let articulation_points = graph.articulation_points();
+        for v in 0..vertices {
+            let splits = components_without(&graph, Some(v), None) > count;
+            assert_eq!(
+                articulation_points.contains(&v),
+                splits,
+                "Vertex {} of {:?}",
+                v,
+                graph.adj_list
+            );
+        }
+
+        let bridges = graph.bridges();
+        for &(u, v) in edges.iter().filter(|&&(u, v)| u != v) {
+            let splits = components_without(&graph, None, Some((u, v))) > count;
+            assert_eq!(
+                bridges.contains(&(u, v)),
+                splits,
+                "Edge {} - {} of {:?}",
+                u,
+                v,
+                graph.adj_list
+            );
+        }
+    }
+
+    println!("Checked components, bipartiteness, cycles, articulation points and bridges on 1000 random graphs");
+
+    // Mostly isolated vertices, one search per component must not cost a pass over all of them.
+    let vertices = 1_000_000;
+    let mut graph = Graph::new(vertices);
+    for v in 1..1000 {
+        graph.add_undirected_edge(v - 1, v);
+    }
+    let start = Instant::now();
+    let components = graph.connected_components();
+    assert!(components[..1000].iter().all(|&component| component == 0));
+    assert!((1000..vertices).all(|v| components[v] == v - 999));
+    println!(
+        "{} components of {} vertices in {:?}",
+        components[vertices - 1] + 1,
+        vertices,
+        start.elapsed()
+    );
+}
+
+// Average vertices expanded by full BFS, BFS that stops at the target, and bidirectional search, on a random graph.
+fn benchmark(vertices: usize, degree: usize, rng: &mut Rng) {
+    let graph = random_graph(vertices, degree, rng);
+    let queries = 10;
+    let mut expanded = [0; 3];
+    let mut times = [0.0; 3];
+
+    for _ in 0..queries {
+        let (source, target) = (rng.gen_range(0..vertices), rng.gen_range(0..vertices));
+
+        let start = Instant::now();
+        let tree = graph.shortest_path(source);
+        times[0] += start.elapsed().as_secs_f64();
+
+        let start = Instant::now();
+        let early = graph.breadth_first_search(&[source], Some(target));
+        times[1] += start.elapsed().as_secs_f64();
+
+        let start = Instant::now();
+        let (path, bidirectional) = graph.bidirectional_search(source, target);
+        times[2] += start.elapsed().as_secs_f64();
+
+        assert_eq!(path.as_ref().map(|path| path.len() - 1), tree.distances[target]);
+        assert_eq!(early.distances[target], tree.distances[target]);
+        expanded[0] += tree.expanded;
+        expanded[1] += early.expanded;
+        expanded[2] += bidirectional;
+    }
+
+    println!(
+        "\n{} vertices, about {} edges each, {} random queries",
+        vertices, degree, queries
+    );
+    let names = ["Full BFS", "BFS to target", "Bidirectional"];
+    for i in 0..3 {
+        println!(
+            "{:<14} expanded {:>9} vertices, {:.2} ms per query",
+            names[i],
+            expanded[i] / queries,
+            times[i] / queries as f64 * 1e3
+        );
     }
 }
 
 fn main() {
-    let mut graph = Graph::new(6);
+    // Ensure proper usage.
+    let mut args = env::args().skip(1).collect::<Vec<String>>();
+    let mut rng = match Rng::from_args(&mut args) {
+        Some(rng) if args.is_empty() => rng,
+        _ => {
+            eprintln!("usage: ./breadth_first_search [--seed <seed>]");
+            process::exit(1);
+        }
+    };
+
+    let mut graph = Graph::new(8);
 
     graph.add_edge(0, 1);
     graph.add_edge(1, 0);
@@ -82,6 +664,47 @@ fn main() {
     graph.add_edge(2, 5);
     graph.add_edge(5, 2);
 
-    println!("ShortestPath(0): {:?}", graph.shortest_path(0));
-    println!("ShortestPath(2): {:?}", graph.shortest_path(2));
+    // Separate component.
+    graph.add_edge(6, 7);
+    graph.add_edge(7, 6);
+
+    let from_zero = graph.shortest_path(0);
+    println!("ShortestPath(0): {:?}", from_zero.distances);
+    println!("ShortestPath(2): {:?}", graph.shortest_path(2).distances);
+    println!("Path from 0 to 3: {:?}", from_zero.path_to(3));
+    println!("Path from 0 to 7: {:?}", from_zero.path_to(7));
+    assert_eq!(from_zero.path_to(3).map(|path| path.len() - 1), from_zero.distances[3]);
+    assert_eq!(from_zero.path_to(7), None);
+
+    // Nearest of several sources.
+    let from_many = graph.breadth_first_search(&[1, 6], None);
+    println!("ShortestPath(1, 6): {:?}", from_many.distances);
+    println!("Path to 5 from 1 or 6: {:?}", from_many.path_to(5));
+
+    // Stops once 2 is found, before its neighbours are discovered.
+    let to_two = graph.breadth_first_search(&[0], Some(2));
+    assert_eq!(to_two.distances[2], from_zero.distances[2]);
+    println!("Path from 0 to 2, stopping early: {:?}", to_two.path_to(2));
+    println!("Explored before stopping: {:?}", to_two.distances);
+
+    println!("Bidirectional path from 0 to 3: {:?}", graph.bidirectional_search(0, 3));
+    println!("Bidirectional path from 0 to 7: {:?}", graph.bidirectional_search(0, 7));
+
+    // Triangle 0 - 1 - 2 with a tail 2 - 3 - 4, and a separate square.
+    let mut undirected = Graph::new(9);
+    for &(u, v) in [(0, 1), (1, 2), (2, 0), (2, 3), (3, 4), (5, 6), (6, 7), (7, 8), (8, 5)].iter() {
+        undirected.add_undirected_edge(u, v);
+    }
+    println!("\nComponents: {:?}", undirected.connected_components());
+    println!("Bipartite: {:?}", undirected.is_bipartite());
+    println!("Cycle: {:?}", undirected.find_cycle());
+    println!("Articulation points: {:?}", undirected.articulation_points());
+    println!("Bridges: {:?}", undirected.bridges());
+    assert_eq!(undirected.articulation_points(), vec![2, 3]);
+    assert_eq!(undirected.bridges(), vec![(2, 3), (3, 4)]);
+
+    println!("\nSeed = {}", rng.seed());
+    check_structure(&mut rng);
+    cross_check(&mut rng);
+    benchmark(1_000_000, 5, &mut rng);
 }
@@ -1,39 +1,245 @@
 /**
  * Kosaraju's Two-Pass algorithm.
  * Compute strongly connected components in directed graph in O(m + n) time.
+ *
+ * Two single pass alternatives find the same components without the reversed graph:
+ * - Tarjan: every vertex keeps the earliest vertex still on the stack that its DFS subtree reaches (its low-link).
+ *   A vertex whose low-link is itself is the root of a component, made of everything above it on the stack.
+ * - Path-based (Gabow): a second stack holds the roots of components not finished yet along the DFS path, and an
+ *   edge back into the path merges everything after its head. A vertex still on top of that stack when it finishes
+ *   closes a component.
+ *
+ * All three find components sinks first, so numbering them from the end gives component IDs in topological order:
+ * every edge goes from a component to itself or to one with a larger ID.
+ *
+ * Collapsing every component into one vertex gives the condensation, a DAG. A vertex reaches another if their
+ * components are the same, or if a path of the DAG leads from one to the other, which can only go towards larger IDs.
+ * DAGs of up to CLOSURE_LIMIT components precompute their transitive closure, one bitset of reachable components per
+ * component, and answer in O(1). Larger ones search the DAG, only among components between the two.
+ *
+ * Input is an edge list, one "tail head" pair per line. Blank lines and lines starting with # are skipped, or with
+ * a # followed by a space when vertices are labels, since a label can start with # too. Vertices are integers from 0
+ * or from 1, or any labels without whitespace, mapped to indices in order of first appearance. The vertex count is the
+ * largest ID, or the number of labels, unless --vertices asks for more.
  */
-use std::collections::LinkedList;
+use std::collections::HashMap;
+use std::fmt::Write;
 use std::fs;
-use std::io::Error;
+use std::path::Path;
+use std::{env, process};
 
+#[allow(dead_code)]
+#[path = "../../1. Divide and Conquer, Sorting and Searching, and Randomized Algorithms/Week 3/rng.rs"]
+pub mod rng;
+use self::rng::Rng;
+
+// Marks a vertex not visited yet, or not in a component yet.
+const NONE: usize = usize::MAX;
+// Largest condensation that gets a transitive closure, n^2 / 8 bytes (8 MB).
+const CLOSURE_LIMIT: usize = 8192;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SccAlgorithm {
+    Kosaraju,
+    Tarjan,
+    PathBased,
+}
+
+// DAG of the strongly connected components of a graph.
 #[derive(Debug)]
-struct Graph {
+pub struct Condensation {
+    // Component of every vertex, numbered in topological order.
+    components: Vec<usize>,
+    // Number of vertices in every component.
+    sizes: Vec<usize>,
+    // Edges from every component to others, as (head component, number of edges between them), sorted by head.
+    pub adj_list: Vec<Vec<(usize, usize)>>,
+    // Components every component reaches, as bitsets, if there are at most CLOSURE_LIMIT components.
+    closure: Option<Vec<Vec<u64>>>,
+}
+
+impl Condensation {
+    pub fn component_count(&self) -> usize {
+        self.sizes.len()
+    }
+
+    // Whether there is a path from vertex u to vertex v.
+    pub fn reaches(&self, u: usize, v: usize) -> bool {
+        let (from, to) = (self.components[u], self.components[v]);
+        match self.closure {
+            Some(ref closure) => closure[from][to / 64] >> (to % 64) & 1 == 1,
+            None => self.search(from, to),
+        }
+    }
+
+    // Whether component [from] reaches component [to], searching only the components between them.
+    fn search(&self, from: usize, to: usize) -> bool {
+        if from >= to {
+            return from == to;
+        }
+
+        let mut seen = vec![false; to - from + 1];
+        let mut stack = vec![from];
+        seen[0] = true;
+        while let Some(component) = stack.pop() {
+            for &(head, _) in self.adj_list[component].iter() {
+                if head == to {
+                    return true;
+                }
+                // Heads are sorted, and anything past [to] can't lead back to it.
+                if head > to {
+                    break;
+                }
+                if !seen[head - from] {
+                    seen[head - from] = true;
+                    stack.push(head);
+                }
+            }
+        }
+
+        false
+    }
+
+    // Bitsets of the components every component reaches, itself included.
+    fn transitive_closure(&self) -> Vec<Vec<u64>> {
+        let count = self.component_count();
+        let words = count.div_ceil(64);
+        let mut closure = vec![vec![0u64; words]; count];
+
+        // Edges only lead to larger IDs, whose rows are already complete.
+        for component in (0..count).rev() {
+            let mut row = vec![0u64; words];
+            row[component / 64] |= 1 << (component % 64);
+            for &(head, _) in self.adj_list[component].iter() {
+                for (word, &head_word) in row.iter_mut().zip(closure[head].iter()) {
+                    *word |= head_word;
+                }
+            }
+            closure[component] = row;
+        }
+
+        closure
+    }
+}
+
+// How vertices are named in an edge list.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum VertexIds {
+    ZeroBased,
+    OneBased,
+    Labels,
+}
+
+// What to print about the components.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Output {
+    // Sizes of the k largest components.
+    Top(usize),
+    // Component of every vertex.
+    Csv,
+    // Every component with its members.
+    Json,
+}
+
+// Graph read from an edge list, with the name of every vertex.
+#[derive(Debug)]
+struct EdgeList {
+    graph: Graph,
+    labels: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct Graph {
     // Number of vertices.
     vertices: usize,
     // Adjacency list from vertex (0..n).
-    adj_list: Vec<LinkedList<usize>>,
-    // Reversed adjacency list of same graph (used to compute finishing times)
-    rev_adj_list: Vec<LinkedList<usize>>,
+    adj_list: Vec<Vec<usize>>,
 }
 
 impl Graph {
     // Create a new graph with [vertices].
-    fn new(vertices: usize) -> Graph {
+    pub fn new(vertices: usize) -> Graph {
         Graph {
             vertices,
-            adj_list: vec![LinkedList::<usize>::new(); vertices],
-            rev_adj_list: vec![LinkedList::<usize>::new(); vertices],
+            adj_list: vec![Vec::<usize>::new(); vertices],
         }
     }
 
-    // Creates an edge from v1 to v2, and reverse edge from v2 to v1
-    fn add_edge(&mut self, v1: usize, v2: usize) {
-        self.adj_list[v1].push_back(v2);
-        self.rev_adj_list[v2].push_back(v1);
+    // Creates an edge from v1 to v2.
+    pub fn add_edge(&mut self, v1: usize, v2: usize) {
+        self.adj_list[v1].push(v2);
     }
 
-    // Compute Strongly Connected Components, and returns size of all SCCs.
-    fn strongly_connected_components(&self) -> Vec<(usize, usize)> {
+    // Compute Strongly Connected Components, and returns the component of every vertex. Components are numbered
+    // from 0 in topological order.
+    pub fn strongly_connected_components(&self, algorithm: SccAlgorithm) -> Vec<usize> {
+        let (mut components, count) = match algorithm {
+            SccAlgorithm::Kosaraju => self.kosaraju(),
+            SccAlgorithm::Tarjan => self.tarjan(),
+            SccAlgorithm::PathBased => self.path_based(),
+        };
+
+        // Components were found sinks first.
+        for component in components.iter_mut() {
+            *component = count - 1 - *component;
+        }
+        components
+    }
+
+    // Collapses every strongly connected component into one vertex, keeping how many edges join every two.
+    pub fn condensation(&self, algorithm: SccAlgorithm) -> Condensation {
+        let components = self.strongly_connected_components(algorithm);
+        let count = components.iter().max().map_or(0, |&max| max + 1);
+
+        let mut sizes = vec![0; count];
+        let mut heads = vec![Vec::new(); count];
+        for (u, neighbours) in self.adj_list.iter().enumerate() {
+            sizes[components[u]] += 1;
+            for &v in neighbours {
+                if components[u] != components[v] {
+                    heads[components[u]].push(components[v]);
+                }
+            }
+        }
+
+        // Count repeated heads.
+        let adj_list = heads
+            .into_iter()
+            .map(|mut heads| {
+                heads.sort_unstable();
+                let mut edges: Vec<(usize, usize)> = Vec::new();
+                for head in heads {
+                    match edges.last_mut() {
+                        Some(&mut (last, ref mut multiplicity)) if last == head => *multiplicity += 1,
+                        _ => edges.push((head, 1)),
+                    }
+                }
+                edges
+            })
+            .collect();
+
+        let mut condensation = Condensation {
+            components,
+            sizes,
+            adj_list,
+            closure: None,
+        };
+        if count <= CLOSURE_LIMIT {
+            condensation.closure = Some(condensation.transitive_closure());
+        }
+        condensation
+    }
+
+    // Kosaraju's two passes, returns components numbered sinks first and their count.
+    fn kosaraju(&self) -> (Vec<usize>, usize) {
+        // Reversed adjacency list of same graph (used to compute finishing times)
+        let mut rev_adj_list = vec![Vec::<usize>::new(); self.vertices];
+        for (v1, neighbours) in self.adj_list.iter().enumerate() {
+            for &v2 in neighbours {
+                rev_adj_list[v2].push(v1);
+            }
+        }
+
         // Store vertices as increasing order of finish times.
         let mut finish_times: Vec<usize> = Vec::with_capacity(self.vertices);
 
@@ -43,114 +249,598 @@ impl Graph {
         // Run DFS on reversed graph to compute finish times.
         for v in 0..self.vertices {
             if !explored[v] {
-                self.dfs_reversed_graph(v, &mut explored, &mut finish_times);
+                Graph::dfs_reversed_graph(&rev_adj_list, v, &mut explored, &mut finish_times);
             }
         }
+        drop(rev_adj_list);
+        drop(explored);
 
-        // Mark all vertices as unexplored again.
-        let mut explored = vec![false; self.vertices];
-
-        // Maintain list of SCC's (leader, its size).
-        let mut scc_list = Vec::<(usize, usize)>::new();
+        // Component of every vertex, in the order they are found.
+        let mut components = vec![NONE; self.vertices];
+        let mut count = 0;
 
         // Run DFS on forward graph with decreasing order of finish times.
-        for v in finish_times.iter().rev() {
-            if !explored[*v] {
-                let mut size_of_this_scc: usize = 0;
-                self.dfs_forward_graph(*v, &mut size_of_this_scc, &mut explored);
-                scc_list.push((*v, size_of_this_scc));
+        for &v in finish_times.iter().rev() {
+            if components[v] == NONE {
+                self.dfs_forward_graph(v, count, &mut components);
+                count += 1;
             }
         }
 
-        return scc_list;
+        (components, count)
     }
 
     // DFS subroutine on forward graph to compute SCC.
-    fn dfs_forward_graph(&self, vertex: usize, size: &mut usize, explored: &mut [bool]) {
+    fn dfs_forward_graph(&self, vertex: usize, component: usize, components: &mut [usize]) {
         // Stack to perform DFS.
-        let mut stack = Vec::<usize>::new();
+        let mut stack = vec![vertex];
 
         // Mark this vertex as explored.
-        explored[vertex] = true;
-        stack.push(vertex);
-        *size = *size + 1;
+        components[vertex] = component;
 
-        'dfs_loop: while !stack.is_empty() {
-            // let mut next_vertex_found = false;
-            if let Some(&top) = stack.last() {
-                for v in &self.adj_list[top] {
-                    // Mark next neighbour to explore next.
-                    if !explored[*v] {
-                        explored[*v] = true;
-                        stack.push(*v);
-                        *size = *size + 1;
-                        continue 'dfs_loop;
-                    }
+        while let Some(top) = stack.pop() {
+            for &v in self.adj_list[top].iter() {
+                // Vertices in components found earlier are in other SCCs.
+                if components[v] == NONE {
+                    components[v] = component;
+                    stack.push(v);
                 }
-
-                // Can't dig deeper.
-                stack.pop();
             }
         }
     }
 
     // DFS subroutine on the reversed graph to compute finishing times.
     fn dfs_reversed_graph(
-        &self,
+        rev_adj_list: &[Vec<usize>],
         vertex: usize,
         explored: &mut [bool],
         finish_times: &mut Vec<usize>,
     ) {
-        // Stack to perform DFS.
-        let mut stack = Vec::<usize>::new();
+        // Stack to perform DFS, with the next neighbour to look at.
+        let mut stack = vec![(vertex, 0)];
 
         // Mark this vertex as explored.
         explored[vertex] = true;
-        stack.push(vertex);
 
-        'dfs_loop: while !stack.is_empty() {
-            // Get the top vertex in stack.
-            if let Some(&top) = stack.last() {
-                for v in &self.rev_adj_list[top] {
+        while let Some(&mut (top, ref mut next)) = stack.last_mut() {
+            match rev_adj_list[top].get(*next) {
+                Some(&v) => {
+                    *next += 1;
                     // Mark next neighbour to explore next.
-                    if !explored[*v] {
-                        explored[*v] = true;
-                        stack.push(*v);
-                        continue 'dfs_loop;
+                    if !explored[v] {
+                        explored[v] = true;
+                        stack.push((v, 0));
+                    }
+                }
+                None => {
+                    // Can't dig deeper.
+                    finish_times.push(top);
+                    stack.pop();
+                }
+            }
+        }
+    }
+
+    // Tarjan's algorithm, returns components numbered sinks first and their count.
+    fn tarjan(&self) -> (Vec<usize>, usize) {
+        // Order in which vertices are visited, and the earliest one on the stack their subtree reaches.
+        let mut index = vec![NONE; self.vertices];
+        let mut low = vec![0; self.vertices];
+        let mut on_stack = vec![false; self.vertices];
+        let mut stack = Vec::new();
+        let mut components = vec![NONE; self.vertices];
+        let (mut visited, mut count) = (0, 0);
+
+        for root in 0..self.vertices {
+            if index[root] != NONE {
+                continue;
+            }
+
+            // DFS path, with the next neighbour to look at.
+            let mut path = vec![(root, 0)];
+            index[root] = visited;
+            low[root] = visited;
+            visited += 1;
+            stack.push(root);
+            on_stack[root] = true;
+
+            while let Some(&mut (u, ref mut next)) = path.last_mut() {
+                if let Some(&v) = self.adj_list[u].get(*next) {
+                    *next += 1;
+                    if index[v] == NONE {
+                        index[v] = visited;
+                        low[v] = visited;
+                        visited += 1;
+                        stack.push(v);
+                        on_stack[v] = true;
+                        path.push((v, 0));
+                    } else if on_stack[v] {
+                        low[u] = low[u].min(index[v]);
+                    }
+                    continue;
+                }
+
+                path.pop();
+                if let Some(&(parent, _)) = path.last() {
+                    low[parent] = low[parent].min(low[u]);
+                }
+
+                // Root of a component, everything above it on the stack is in it.
+                if low[u] == index[u] {
+                    loop {
+                        let v = stack.pop().unwrap();
+                        on_stack[v] = false;
+                        components[v] = count;
+                        if v == u {
+                            break;
+                        }
+                    }
+                    count += 1;
+                }
+            }
+        }
+
+        (components, count)
+    }
+
+    // Gabow's path-based algorithm, returns components numbered sinks first and their count.
+    fn path_based(&self) -> (Vec<usize>, usize) {
+        let mut preorder = vec![NONE; self.vertices];
+        let mut components = vec![NONE; self.vertices];
+        // Vertices not in a component yet, and the roots of the ones still open on the DFS path.
+        let mut stack = Vec::new();
+        let mut roots = Vec::new();
+        let (mut visited, mut count) = (0, 0);
+
+        for root in 0..self.vertices {
+            if preorder[root] != NONE {
+                continue;
+            }
+
+            let mut path = vec![(root, 0)];
+            preorder[root] = visited;
+            visited += 1;
+            stack.push(root);
+            roots.push(root);
+
+            while let Some(&mut (u, ref mut next)) = path.last_mut() {
+                if let Some(&v) = self.adj_list[u].get(*next) {
+                    *next += 1;
+                    if preorder[v] == NONE {
+                        preorder[v] = visited;
+                        visited += 1;
+                        stack.push(v);
+                        roots.push(v);
+                        path.push((v, 0));
+                    } else if components[v] == NONE {
+                        // Back into the open path, so everything visited after v joins its component.
+                        while preorder[*roots.last().unwrap()] > preorder[v] {
+                            roots.pop();
+                        }
                     }
+                    continue;
                 }
 
-                // Can't dig deeper.
-                finish_times.push(top);
-                stack.pop();
+                path.pop();
+                if roots.last() == Some(&u) {
+                    roots.pop();
+                    loop {
+                        let v = stack.pop().unwrap();
+                        components[v] = count;
+                        if v == u {
+                            break;
+                        }
+                    }
+                    count += 1;
+                }
             }
         }
+
+        (components, count)
     }
 }
 
-fn main() -> Result<(), Error> {
-    let filename = "scc_input_graph.txt";
-    let no_of_vertices: usize = 875714;
+// Parses an edge list with at least [min_vertices] vertices. Errors start with their line number.
+fn parse_edge_list(contents: &str, ids: VertexIds, min_vertices: usize) -> Result<EdgeList, String> {
+    let mut edges = Vec::new();
+    let mut indices: HashMap<&str, usize> = HashMap::new();
+    let mut labels = Vec::new();
+    let mut vertices = min_vertices;
 
-    let mut graph = Graph::new(no_of_vertices);
+    for (index, line) in contents.lines().enumerate() {
+        let tokens = line.split_whitespace().collect::<Vec<&str>>();
+        let comment = match ids {
+            VertexIds::Labels => tokens.first() == Some(&"#"),
+            _ => tokens.first().is_some_and(|token| token.starts_with('#')),
+        };
+        if tokens.is_empty() || comment {
+            continue;
+        }
+        if tokens.len() != 2 {
+            return Err(format!("{}: expected two vertices, found {}", index + 1, tokens.len()));
+        }
 
-    // Try to read the graph from file.
-    let file_content = fs::read_to_string(filename)?;
-    for line in file_content.lines() {
-        let vertices = line
-            .split_whitespace()
-            .map(|v| v.parse::<usize>().unwrap())
-            .collect::<Vec<usize>>();
+        let mut edge = [0; 2];
+        for (vertex, token) in edge.iter_mut().zip(tokens) {
+            *vertex = match ids {
+                VertexIds::Labels => *indices.entry(token).or_insert_with(|| {
+                    labels.push(token.to_string());
+                    labels.len() - 1
+                }),
+                _ => {
+                    let base = if ids == VertexIds::OneBased { 1 } else { 0 };
+                    match token.parse::<usize>() {
+                        Ok(id) if id >= base => id - base,
+                        Ok(id) => return Err(format!("{}: vertex {} with IDs starting at {}", index + 1, id, base)),
+                        Err(error) => return Err(format!("{}: '{}' is not a vertex ({})", index + 1, token, error)),
+                    }
+                }
+            };
+            vertices = vertices.max(*vertex + 1);
+        }
+        edges.push((edge[0], edge[1]));
+    }
 
-        graph.add_edge(vertices[0] - 1, vertices[1] - 1);
+    match ids {
+        VertexIds::Labels => vertices = vertices.max(labels.len()),
+        VertexIds::ZeroBased => labels = (0..vertices).map(|vertex| vertex.to_string()).collect(),
+        VertexIds::OneBased => labels = (1..=vertices).map(|vertex| vertex.to_string()).collect(),
+    }
+    // Vertices asked for beyond the labels seen have no name, number them after the labels. Add #s in front until
+    // the name is not a real label.
+    for vertex in labels.len()..vertices {
+        let mut label = format!("#{}", vertex);
+        while indices.contains_key(label.as_str()) {
+            label.insert(0, '#');
+        }
+        labels.push(label);
     }
 
-    let mut result = graph.strongly_connected_components();
+    let mut graph = Graph::new(vertices);
+    for (v1, v2) in edges {
+        graph.add_edge(v1, v2);
+    }
+    Ok(EdgeList { graph, labels })
+}
+
+// Quotes a CSV field if it needs it.
+fn csv_field(field: &str) -> String {
+    match field.contains([',', '"', '\n', '\r']) {
+        true => format!("\"{}\"", field.replace('"', "\"\"")),
+        false => field.to_string(),
+    }
+}
+
+// Quotes and escapes a JSON string.
+fn json_string(string: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in string.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            c if (c as u32) < 0x20 => write!(quoted, "\\u{:04x}", c as u32).unwrap(),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+// Describes the components of every vertex as asked.
+fn format_components(components: &[usize], labels: &[String], output: Output) -> String {
+    let count = components.iter().max().map_or(0, |&max| max + 1);
+    let mut text = String::new();
+    match output {
+        Output::Top(k) => {
+            let mut sizes = vec![0; count];
+            for &component in components {
+                sizes[component] += 1;
+            }
 
-    // Sort descending by SCC size.
-    result.sort_unstable_by(|a, b| b.1.cmp(&a.1));
+            // Sort descending by SCC size.
+            sizes.sort_unstable_by(|a, b| b.cmp(a));
+            writeln!(text, "{:?}", &sizes[..k.min(sizes.len())]).unwrap();
+        }
+        Output::Csv => {
+            text.push_str("vertex,component\n");
+            for (label, component) in labels.iter().zip(components) {
+                writeln!(text, "{},{}", csv_field(label), component).unwrap();
+            }
+        }
+        Output::Json => {
+            let mut members = vec![Vec::new(); count];
+            for (label, &component) in labels.iter().zip(components) {
+                members[component].push(json_string(label));
+            }
+
+            // Components in topological order.
+            text.push_str("[\n");
+            for (component, members) in members.iter().enumerate() {
+                let separator = if component + 1 < count { "," } else { "" };
+                writeln!(
+                    text,
+                    "  {{\"component\": {}, \"size\": {}, \"members\": [{}]}}{}",
+                    component,
+                    members.len(),
+                    members.join(", "),
+                    separator
+                )
+                .unwrap();
+            }
+            text.push_str("]\n");
+        }
+    }
+    text
+}
+
+// Checks parsing of every kind of vertex ID, its errors, and the output formats.
+fn check_parsing() {
+    let edges = |list: &EdgeList| {
+        (0..list.graph.vertices)
+            .flat_map(|u| list.graph.adj_list[u].iter().map(move |&v| (u, v)))
+            .collect::<Vec<(usize, usize)>>()
+    };
+
+    let list = parse_edge_list("1 2\n\n  2 3 \n# comment\n3 1\n", VertexIds::OneBased, 0).unwrap();
+    assert_eq!(edges(&list), vec![(0, 1), (1, 2), (2, 0)]);
+    assert_eq!(list.labels, vec!["1", "2", "3"]);
+
+    let list = parse_edge_list("0 4\n", VertexIds::ZeroBased, 7).unwrap();
+    assert_eq!(list.graph.vertices, 7);
+    assert_eq!(parse_edge_list("", VertexIds::ZeroBased, 0).unwrap().graph.vertices, 0);
+
+    let list = parse_edge_list("make lib\nlib \"a,b\"\nlib make\n", VertexIds::Labels, 4).unwrap();
+    assert_eq!(edges(&list), vec![(0, 1), (1, 2), (1, 0)]);
+    assert_eq!(list.labels, vec!["make", "lib", "\"a,b\"", "#3"]);
+
+    let components = list.graph.strongly_connected_components(SccAlgorithm::Tarjan);
+    assert_eq!(format_components(&components, &list.labels, Output::Top(2)), "[2, 1]\n");
+    assert_eq!(
+        format_components(&components, &list.labels, Output::Csv),
+        "vertex,component\nmake,1\nlib,1\n\"\"\"a,b\"\"\",2\n#3,0\n"
+    );
+    assert_eq!(
+        format_components(&components, &list.labels, Output::Json),
+        "[\n  {\"component\": 0, \"size\": 1, \"members\": [\"#3\"]},\n  \
+         {\"component\": 1, \"size\": 2, \"members\": [\"make\", \"lib\"]},\n  \
+         {\"component\": 2, \"size\": 1, \"members\": [\"\\\"a,b\\\"\"]}\n]\n"
+    );
+
+    // Labels starting with # are vertices, not comments, and padding names don't take them.
+    let list = parse_edge_list("# comment\n#include lib\nlib #3\n", VertexIds::Labels, 5).unwrap();
+    assert_eq!(edges(&list), vec![(0, 1), (1, 2)]);
+    assert_eq!(list.labels, vec!["#include", "lib", "#3", "##3", "#4"]);
+
+    let errors = [
+        ("1 2\n3\n", VertexIds::OneBased, "2: expected two vertices, found 1"),
+        (
+            "1 2\n\n0 1\n",
+            VertexIds::OneBased,
+            "3: vertex 0 with IDs starting at 1",
+        ),
+        (
+            "1 x\n",
+            VertexIds::ZeroBased,
+            "1: 'x' is not a vertex (invalid digit found in string)",
+        ),
+        ("a b c\n", VertexIds::Labels, "1: expected two vertices, found 3"),
+    ];
+    for &(contents, ids, error) in errors.iter() {
+        assert_eq!(parse_edge_list(contents, ids, 0).unwrap_err(), error);
+    }
+
+    println!("Checked edge list parsing and output formats");
+}
+
+// Random directed graph with the given number of edges.
+pub fn random_graph(vertices: usize, edges: usize, rng: &mut Rng) -> Graph {
+    let mut graph = Graph::new(vertices);
+    for _ in 0..edges {
+        graph.add_edge(rng.gen_range(0..vertices), rng.gen_range(0..vertices));
+    }
+    graph
+}
 
-    println!("{:?}", &result[..5]);
+// Vertices reachable from every vertex, by brute force.
+fn reachable(graph: &Graph) -> Vec<Vec<bool>> {
+    (0..graph.vertices)
+        .map(|source| {
+            let mut seen = vec![false; graph.vertices];
+            let mut stack = vec![source];
+            seen[source] = true;
+            while let Some(u) = stack.pop() {
+                for &v in graph.adj_list[u].iter() {
+                    if !seen[v] {
+                        seen[v] = true;
+                        stack.push(v);
+                    }
+                }
+            }
+            seen
+        })
+        .collect()
+}
+
+// Checks all three algorithms against mutual reachability, and the topological order of their IDs, on small random
+// graphs.
+fn cross_check(rng: &mut Rng) {
+    let algorithms = [SccAlgorithm::Kosaraju, SccAlgorithm::Tarjan, SccAlgorithm::PathBased];
+
+    for round in 0..1000 {
+        let vertices = 1 + round % 30;
+        let graph = random_graph(vertices, rng.gen_range(0..3 * vertices), rng);
+        let reach = reachable(&graph);
+
+        for &algorithm in algorithms.iter() {
+            let components = graph.strongly_connected_components(algorithm);
+            for u in 0..vertices {
+                for v in 0..vertices {
+                    let strongly_connected = reach[u][v] && reach[v][u];
+                    assert_eq!(
+                        components[u] == components[v],
+                        strongly_connected,
+                        "{:?} failed",
+                        algorithm
+                    );
+                }
+                for &v in graph.adj_list[u].iter() {
+                    assert!(
+                        components[u] <= components[v],
+                        "{:?} IDs are not topological",
+                        algorithm
+                    );
+                }
+            }
+
+            let mut ids = components.clone();
+            ids.sort_unstable();
+            ids.dedup();
+            assert_eq!(ids, (0..ids.len()).collect::<Vec<usize>>());
+        }
+    }
+
+    println!("Cross-checked Kosaraju, Tarjan and path-based SCC on 1000 random graphs");
+}
+
+// Checks condensations and reachability, with the closure and by search, against brute force.
+fn check_condensation(rng: &mut Rng) {
+    for round in 0..1000 {
+        let vertices = 1 + round % 30;
+        let graph = random_graph(vertices, rng.gen_range(0..2 * vertices), rng);
+        let reach = reachable(&graph);
+        let condensation = graph.condensation(SccAlgorithm::Tarjan);
+        let components = &condensation.components;
+
+        // Every edge between components is counted once, and goes towards a larger ID.
+        let crossing = (0..vertices)
+            .flat_map(|u| graph.adj_list[u].iter().map(move |&v| (u, v)))
+            .filter(|&(u, v)| components[u] != components[v])
+            .count();
+        let mut counted = 0;
+        for (component, edges) in condensation.adj_list.iter().enumerate() {
+            assert!(edges.windows(2).all(|pair| pair[0].0 < pair[1].0));
+            for &(head, multiplicity) in edges.iter() {
+                assert!(component < head);
+                counted += multiplicity;
+            }
+        }
+        assert_eq!(counted, crossing);
+        assert_eq!(condensation.sizes.iter().sum::<usize>(), vertices);
+
+        for u in 0..vertices {
+            for v in 0..vertices {
+                assert_eq!(
+                    condensation.reaches(u, v),
+                    reach[u][v],
+                    "Wrong reachability from {} to {}",
+                    u,
+                    v
+                );
+                assert_eq!(condensation.search(components[u], components[v]), reach[u][v]);
+            }
+        }
+    }
+
+    println!("Checked condensations and reachability on 1000 random graphs");
+}
+
+fn main() {
+    let usage = "usage: ./kosaraju_algorithm [<edge list file>] [--zero-based | --labels] [--vertices <count>] \
+                 [--algorithm kosaraju | tarjan | path-based] [--top <k> | --csv | --json] [--mapping <file>] \
+                 [--seed <seed>]";
+    let fail = || -> ! {
+        eprintln!("{}", usage);
+        process::exit(1);
+    };
+
+    // Parse arguments.
+    let mut args = env::args().skip(1).collect::<Vec<String>>();
+    let mut rng = Rng::from_args(&mut args).unwrap_or_else(|| fail());
+    let mut filename = None;
+    let mut ids = VertexIds::OneBased;
+    let mut min_vertices = 0;
+    let mut algorithm = SccAlgorithm::Kosaraju;
+    let mut output = Output::Top(5);
+    let mut mapping = None;
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--zero-based" => ids = VertexIds::ZeroBased,
+            "--labels" => ids = VertexIds::Labels,
+            "--vertices" => {
+                min_vertices = args
+                    .next()
+                    .and_then(|count| count.parse().ok())
+                    .unwrap_or_else(|| fail())
+            }
+            "--algorithm" => {
+                algorithm = match args.next().as_deref() {
+                    Some("kosaraju") => SccAlgorithm::Kosaraju,
+                    Some("tarjan") => SccAlgorithm::Tarjan,
+                    Some("path-based") => SccAlgorithm::PathBased,
+                    _ => fail(),
+                }
+            }
+            "--top" => output = Output::Top(args.next().and_then(|k| k.parse().ok()).unwrap_or_else(|| fail())),
+            "--csv" => output = Output::Csv,
+            "--json" => output = Output::Json,
+            "--mapping" => mapping = Some(args.next().unwrap_or_else(|| fail())),
+            _ if arg.starts_with("--") || filename.is_some() => fail(),
+            _ => filename = Some(arg),
+        }
+    }
+
+    // Without a file, check everything, then run on the course's graph if it is here.
+    let filename = match filename {
+        Some(filename) => filename,
+        None => {
+            println!("Seed = {}", rng.seed());
+            cross_check(&mut rng);
+            check_condensation(&mut rng);
+            check_parsing();
+
+            // Dependencies with a cycle: 1 -> 2 -> 3 -> 1, and 0 -> 1, 3 -> 4, 0 -> 4 twice.
+            let mut dependencies = Graph::new(5);
+            for &(v1, v2) in [(1, 2), (2, 3), (3, 1), (0, 1), (3, 4), (0, 4), (0, 4)].iter() {
+                dependencies.add_edge(v1, v2);
+            }
+            let condensation = dependencies.condensation(SccAlgorithm::Tarjan);
+            println!("\nComponents: {:?}", condensation.components);
+            println!("Condensation edges: {:?}", condensation.adj_list);
+            println!("0 depends on 3: {}", condensation.reaches(0, 3));
+            println!("4 depends on 0: {}", condensation.reaches(4, 0));
+
+            let course_graph = "scc_input_graph.txt";
+            if !Path::new(course_graph).exists() {
+                return;
+            }
+            println!();
+            String::from(course_graph)
+        }
+    };
+
+    // Try to read the graph from file.
+    let contents = fs::read_to_string(&filename).unwrap_or_else(|error| {
+        eprintln!("{}: {}", filename, error);
+        process::exit(1);
+    });
+    let list = parse_edge_list(&contents, ids, min_vertices).unwrap_or_else(|error| {
+        eprintln!("{}:{}", filename, error);
+        process::exit(1);
+    });
+
+    // Index of every vertex label.
+    if let Some(mapping) = mapping {
+        let mut text = String::from("index,label\n");
+        for (index, label) in list.labels.iter().enumerate() {
+            writeln!(text, "{},{}", index, csv_field(label)).unwrap();
+        }
+        if let Err(error) = fs::write(&mapping, text) {
+            eprintln!("{}: {}", mapping, error);
+            process::exit(1);
+        }
+    }
 
-    Ok(())
+    let components = list.graph.strongly_connected_components(algorithm);
+    print!("{}", format_components(&components, &list.labels, output));
 }
@@ -0,0 +1,109 @@
+/**
+ * Benchmark for the strongly connected components algorithms.
+ *
+ * Runs Kosaraju, Tarjan and path-based SCC on a random graph as large as the assignment input, and prints how long
+ * each takes and the most memory it has allocated at once, counted by a global allocator wrapped around the system
+ * one. Then times the condensation and random reachability queries on the same graph.
+ */
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::env;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+#[allow(dead_code)]
+mod kosaraju_algorithm;
+use kosaraju_algorithm::rng::Rng;
+use kosaraju_algorithm::{random_graph, SccAlgorithm};
+
+// System allocator that keeps track of the bytes in use, and the most ever in use.
+struct CountingAllocator {
+    current: AtomicUsize,
+    peak: AtomicUsize,
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let current = self.current.fetch_add(layout.size(), Ordering::Relaxed) + layout.size();
+        self.peak.fetch_max(current, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        self.current.fetch_sub(layout.size(), Ordering::Relaxed);
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator {
+    current: AtomicUsize::new(0),
+    peak: AtomicUsize::new(0),
+};
+
+// Runtime and memory allocated at most by every algorithm, on a random graph as large as the assignment input.
+fn benchmark(rng: &mut Rng) {
+    let (vertices, edges) = (875_714, 5_105_043);
+    let graph = random_graph(vertices, edges, rng);
+    println!("Random graph with {} vertices and {} edges", vertices, edges);
+
+    let mut expected = None;
+    for &algorithm in [SccAlgorithm::Kosaraju, SccAlgorithm::Tarjan, SccAlgorithm::PathBased].iter() {
+        let before = ALLOCATOR.current.load(Ordering::Relaxed);
+        ALLOCATOR.peak.store(before, Ordering::Relaxed);
+
+        let start = Instant::now();
+        let components = graph.strongly_connected_components(algorithm);
+        let elapsed = start.elapsed();
+        let peak = ALLOCATOR.peak.load(Ordering::Relaxed) - before;
+
+        let count = components.iter().max().map_or(0, |&max| max + 1);
+        println!(
+            "{:<10} {:>8} components in {:>9.2?}, {:>6.1} MB at most",
+            format!("{:?}", algorithm),
+            count,
+            elapsed,
+            peak as f64 / 1e6
+        );
+        match expected {
+            Some(expected) => assert_eq!(count, expected),
+            None => expected = Some(count),
+        }
+    }
+
+    let start = Instant::now();
+    let condensation = graph.condensation(SccAlgorithm::Tarjan);
+    let edges = condensation.adj_list.iter().map(|edges| edges.len()).sum::<usize>();
+    println!(
+        "Condensation with {} components and {} edges, transitive closure included, in {:.2?}",
+        condensation.component_count(),
+        edges,
+        start.elapsed()
+    );
+
+    let start = Instant::now();
+    let queries = 1_000_000;
+    let reached = (0..queries)
+        .filter(|_| condensation.reaches(rng.gen_range(0..vertices), rng.gen_range(0..vertices)))
+        .count();
+    println!(
+        "{} of {} random reachability queries true, in {:.2?}",
+        reached,
+        queries,
+        start.elapsed()
+    );
+}
+
+fn main() {
+    // Ensure proper usage.
+    let mut args = env::args().skip(1).collect::<Vec<String>>();
+    let mut rng = match Rng::from_args(&mut args) {
+        Some(rng) if args.is_empty() => rng,
+        _ => {
+            eprintln!("usage: ./kosaraju_benchmark [--seed <seed>]");
+            std::process::exit(1);
+        }
+    };
+
+    println!("Seed = {}", rng.seed());
+    benchmark(&mut rng);
+}
@@ -1,8 +1,52 @@
 /**
  * Compute Topological ordering in directed acyclic graph using Depth First Search.
  * Time Complexity: O(m + n)
+ *
+ * An edge u -> v means u has to come before v: it goes from a dependency to what depends on it.
+ *
+ * Vertices are listed in reverse order of finishing. An edge to a vertex whose DFS is still running closes a cycle,
+ * made of that vertex and everything above it on the DFS stack, and there is no topological order. The DFS keeps
+ * its own stack, so long chains of dependencies don't overflow the call stack.
+ *
+ * Kahn's algorithm instead repeatedly removes a vertex with no incoming edges left. Taking the smallest one each time,
+ * from a heap, gives the lexicographically smallest order, the same for every run and every edge order. Removing all
+ * of them at once gives layers, where every vertex only depends on earlier layers, so a layer can run in parallel.
+ * Backtracking over the choices at every step enumerates all orders, in lexicographic order.
  */
-use std::collections::HashMap;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, VecDeque};
+use std::fmt;
+use std::time::Instant;
+use std::{env, process};
+
+#[allow(dead_code)]
+#[path = "../../1. Divide and Conquer, Sorting and Searching, and Randomized Algorithms/Week 3/rng.rs"]
+mod rng;
+use rng::Rng;
+
+// Cycle of a graph, every vertex has an edge to the next one and the last to the first.
+#[derive(Debug, Clone, PartialEq)]
+struct Cycle {
+    vertices: Vec<usize>,
+}
+
+impl fmt::Display for Cycle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for vertex in self.vertices.iter() {
+            write!(f, "{} -> ", vertex)?;
+        }
+        write!(f, "{}", self.vertices[0])
+    }
+}
+
+// Progress of the DFS at a vertex.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum State {
+    Unexplored,
+    // Still on the DFS stack.
+    Exploring,
+    Finished,
+}
 
 // Graph representation in adjacency list.
 #[derive(Debug)]
@@ -27,49 +71,400 @@ impl Graph {
         self.adj_list[v1].push(v2);
     }
 
-    // Returns vertices in topological order.
-    fn topological_order(&self) -> HashMap<usize, usize> {
-        let mut explored = vec![false; self.vertices];
-        let mut f_values: HashMap<usize, usize> = HashMap::new();
-
-        // Count F-values from top-down
-        let mut current_f_value = self.vertices;
+    // Returns vertices in topological order, or a cycle if there is none.
+    fn topological_order(&self) -> Result<Vec<usize>, Cycle> {
+        let mut states = vec![State::Unexplored; self.vertices];
+        let mut order = Vec::with_capacity(self.vertices);
 
         // Run DFS on all vertices.
         for vertex in 0..self.vertices {
-            if !explored[vertex] {
-                self.depth_first_search(vertex, &mut explored, &mut f_values, &mut current_f_value);
+            if states[vertex] == State::Unexplored {
+                self.depth_first_search(vertex, &mut states, &mut order)?;
+            }
+        }
+
+        // Last finished comes first.
+        order.reverse();
+        Ok(order)
+    }
+
+    // Run DFS from this vertex, appending vertices as they finish.
+    fn depth_first_search(&self, vertex: usize, states: &mut [State], order: &mut Vec<usize>) -> Result<(), Cycle> {
+        // Vertices being explored, with the index of their next edge.
+        let mut stack = vec![(vertex, 0)];
+        states[vertex] = State::Exploring;
+
+        while let Some(&mut (u, ref mut next)) = stack.last_mut() {
+            match self.adj_list[u].get(*next) {
+                Some(&v) => {
+                    *next += 1;
+                    match states[v] {
+                        State::Unexplored => {
+                            states[v] = State::Exploring;
+                            stack.push((v, 0));
+                        }
+                        // Back edge, v is on the stack below u.
+                        State::Exploring => {
+                            let start = stack.iter().rposition(|&(w, _)| w == v).unwrap();
+                            let vertices = stack[start..].iter().map(|&(w, _)| w).collect();
+                            return Err(Cycle { vertices });
+                        }
+                        State::Finished => {}
+                    }
+                }
+                // We reach the last vertex in this DFS iteration.
+                None => {
+                    states[u] = State::Finished;
+                    order.push(u);
+                    stack.pop();
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    // Number of edges into every vertex.
+    fn in_degrees(&self) -> Vec<usize> {
+        let mut in_degrees = vec![0; self.vertices];
+        for &v in self.adj_list.iter().flatten() {
+            in_degrees[v] += 1;
+        }
+        in_degrees
+    }
+
+    // Kahn's algorithm, returns vertices in topological order, or a cycle if there is none.
+    fn kahn(&self) -> Result<Vec<usize>, Cycle> {
+        let mut in_degrees = self.in_degrees();
+        let mut queue = (0..self.vertices)
+            .filter(|&vertex| in_degrees[vertex] == 0)
+            .collect::<VecDeque<usize>>();
+        let mut order = Vec::with_capacity(self.vertices);
+
+        while let Some(u) = queue.pop_front() {
+            order.push(u);
+            for &v in self.adj_list[u].iter() {
+                in_degrees[v] -= 1;
+                if in_degrees[v] == 0 {
+                    queue.push_back(v);
+                }
+            }
+        }
+
+        self.complete(order)
+    }
+
+    // Returns the lexicographically smallest topological order, or a cycle if there is none.
+    fn smallest_order(&self) -> Result<Vec<usize>, Cycle> {
+        let mut in_degrees = self.in_degrees();
+        let mut heap = (0..self.vertices)
+            .filter(|&vertex| in_degrees[vertex] == 0)
+            .map(Reverse)
+            .collect::<BinaryHeap<Reverse<usize>>>();
+        let mut order = Vec::with_capacity(self.vertices);
+
+        while let Some(Reverse(u)) = heap.pop() {
+            order.push(u);
+            for &v in self.adj_list[u].iter() {
+                in_degrees[v] -= 1;
+                if in_degrees[v] == 0 {
+                    heap.push(Reverse(v));
+                }
             }
         }
 
-        return f_values;
+        self.complete(order)
     }
 
-    // Run DFS from this vertex
-    fn depth_first_search(
-        &self,
-        vertex: usize,
-        explored: &mut [bool],
-        f_values: &mut HashMap<usize, usize>,
-        current_f_value: &mut usize,
-    ) {
-        // Mark this vertex as explored.
-        explored[vertex] = true;
+    // Splits the vertices into layers that only have edges into later ones, each as early as possible and sorted, or
+    // returns a cycle if there are none.
+    fn layers(&self) -> Result<Vec<Vec<usize>>, Cycle> {
+        let mut in_degrees = self.in_degrees();
+        let mut layer = (0..self.vertices)
+            .filter(|&vertex| in_degrees[vertex] == 0)
+            .collect::<Vec<usize>>();
+        let mut layers = Vec::new();
+        let mut placed = 0;
 
-        // Explore all neighbouring vertices of this vertex.
-        for v in self.adj_list.get(vertex).unwrap() {
-            if !explored[*v] {
-                self.depth_first_search(*v, explored, f_values, current_f_value);
+        while !layer.is_empty() {
+            let mut next_layer = Vec::new();
+            for &u in layer.iter() {
+                for &v in self.adj_list[u].iter() {
+                    in_degrees[v] -= 1;
+                    if in_degrees[v] == 0 {
+                        next_layer.push(v);
+                    }
+                }
             }
+            next_layer.sort_unstable();
+            placed += layer.len();
+            layers.push(layer);
+            layer = next_layer;
+        }
+
+        match placed == self.vertices {
+            true => Ok(layers),
+            false => Err(self.topological_order().unwrap_err()),
+        }
+    }
+
+    // Iterates over all topological orders, in lexicographic order. There are none if the graph has a cycle, which is
+    // checked first rather than left to the backtracking to find out.
+    fn all_orders(&self) -> AllOrders<'_> {
+        AllOrders {
+            graph: self,
+            in_degrees: self.in_degrees(),
+            placed: vec![false; self.vertices],
+            order: Vec::with_capacity(self.vertices),
+            next: vec![0; self.vertices + 1],
+            done: self.topological_order().is_err(),
         }
+    }
 
-        // We reach the last vertex in this DFS iteration, save F-value for this.
-        f_values.insert(vertex, *current_f_value);
-        *current_f_value = *current_f_value - 1;
+    // Kahn's order if it holds every vertex. Vertices left out are on or after a cycle, which DFS finds.
+    fn complete(&self, order: Vec<usize>) -> Result<Vec<usize>, Cycle> {
+        match order.len() == self.vertices {
+            true => Ok(order),
+            false => Err(self.topological_order().unwrap_err()),
+        }
     }
 }
 
+// Iterator over the topological orders of a graph, see Graph::all_orders.
+struct AllOrders<'a> {
+    graph: &'a Graph,
+    // Edges into every vertex from vertices not placed yet.
+    in_degrees: Vec<usize>,
+    placed: Vec<bool>,
+    // Order placed so far.
+    order: Vec<usize>,
+    // Smallest vertex still to try at every position of the order.
+    next: Vec<usize>,
+    done: bool,
+}
+
+impl<'a> AllOrders<'a> {
+    fn place(&mut self, u: usize) {
+        self.placed[u] = true;
+        for &v in self.graph.adj_list[u].iter() {
+            self.in_degrees[v] -= 1;
+        }
+        self.order.push(u);
+        self.next[self.order.len()] = 0;
+    }
+
+    // Takes back the last vertex placed, returns false if there was none.
+    fn unplace(&mut self) -> bool {
+        match self.order.pop() {
+            Some(u) => {
+                self.placed[u] = false;
+                for &v in self.graph.adj_list[u].iter() {
+                    self.in_degrees[v] += 1;
+                }
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+impl<'a> Iterator for AllOrders<'a> {
+    type Item = Vec<usize>;
+
+    fn next(&mut self) -> Option<Vec<usize>> {
+        let vertices = self.graph.vertices;
+        while !self.done {
+            let position = self.order.len();
+            if position == vertices {
+                let order = self.order.clone();
+                self.done = !self.unplace();
+                return Some(order);
+            }
+
+            // Next vertex with no edges left into it, or back to the previous position.
+            match (self.next[position]..vertices).find(|&v| !self.placed[v] && self.in_degrees[v] == 0) {
+                Some(v) => {
+                    self.next[position] = v + 1;
+                    self.place(v);
+                }
+                None => self.done = !self.unplace(),
+            }
+        }
+        None
+    }
+}
+
+// Whether every edge goes forward in the order, which holds every vertex once.
+fn is_topological(graph: &Graph, order: &[usize]) -> bool {
+    let mut position = vec![None; graph.vertices];
+    for (index, &vertex) in order.iter().enumerate() {
+        if position[vertex].replace(index).is_some() {
+            return false;
+        }
+    }
+    order.len() == graph.vertices
+        && (0..graph.vertices).all(|u| graph.adj_list[u].iter().all(|&v| position[u] < position[v]))
+}
+
+// Whether the cycle is simple and made of edges of the graph.
+fn is_cycle(graph: &Graph, cycle: &Cycle) -> bool {
+    let vertices = &cycle.vertices;
+    let mut seen = vec![false; graph.vertices];
+    !vertices.is_empty()
+        && vertices
+            .iter()
+            .all(|&vertex| !std::mem::replace(&mut seen[vertex], true))
+        && (0..vertices.len()).all(|i| graph.adj_list[vertices[i]].contains(&vertices[(i + 1) % vertices.len()]))
+}
+
+// Number of topological orders, by dynamic programming over the sets of vertices placed first.
+fn count_orders(graph: &Graph) -> u64 {
+    let n = graph.vertices;
+    let mut predecessors = vec![0usize; n];
+    for u in 0..n {
+        for &v in graph.adj_list[u].iter() {
+            predecessors[v] |= 1 << u;
+        }
+    }
+
+    let mut counts = vec![0u64; 1 << n];
+    counts[0] = 1;
+    for set in 0..1usize << n {
+        for v in (0..n).filter(|&v| set & 1 << v == 0 && predecessors[v] & !set == 0) {
+            counts[set | 1 << v] += counts[set];
+        }
+    }
+    counts[(1 << n) - 1]
+}
+
+// Whether every vertex is in one layer, edges only go to later layers, and every vertex past the first layer has an
+// edge from the layer just before.
+fn is_layering(graph: &Graph, layers: &[Vec<usize>]) -> bool {
+    let mut depths = vec![None; graph.vertices];
+    for (depth, layer) in layers.iter().enumerate() {
+        for &vertex in layer.iter() {
+            if depths[vertex].replace(depth).is_some() {
+                return false;
+            }
+        }
+    }
+    let mut latest = vec![None; graph.vertices];
+    for u in 0..graph.vertices {
+        for &v in graph.adj_list[u].iter() {
+            if depths[u] >= depths[v] {
+                return false;
+            }
+            latest[v] = latest[v].max(depths[u]);
+        }
+    }
+    (0..graph.vertices).all(|v| depths[v].is_some() && depths[v].map(|depth| depth.checked_sub(1)) == Some(latest[v]))
+}
+
+// Random graph, with every edge going forward in a hidden random order if acyclic.
+fn random_graph(vertices: usize, edges: usize, acyclic: bool, rng: &mut Rng) -> Graph {
+    let mut ranks = (0..vertices).collect::<Vec<usize>>();
+    rng.shuffle(&mut ranks);
+
+    let mut graph = Graph::new(vertices);
+    for _ in 0..edges {
+        let (u, v) = (rng.gen_range(0..vertices), rng.gen_range(0..vertices));
+        match acyclic {
+            true if ranks[u] < ranks[v] => graph.add_edge(u, v),
+            true if ranks[v] < ranks[u] => graph.add_edge(v, u),
+            true => {}
+            false => graph.add_edge(u, v),
+        }
+    }
+    graph
+}
+
+// Checks orders and cycles on random graphs, acyclic or not.
+fn check(rng: &mut Rng) {
+    let mut cycles = 0;
+    for round in 0..2000 {
+        let vertices = 1 + round % 40;
+        let acyclic = round % 2 == 0;
+        let graph = random_graph(vertices, rng.gen_range(0..3 * vertices), acyclic, rng);
+
+        match graph.topological_order() {
+            Ok(order) => assert!(is_topological(&graph, &order), "Wrong order {:?} of {:?}", order, graph),
+            Err(cycle) => {
+                assert!(!acyclic, "Cycle {} in a DAG", cycle);
+                assert!(is_cycle(&graph, &cycle), "{} is not a cycle of {:?}", cycle, graph);
+                cycles += 1;
+            }
+        }
+    }
+    println!("Checked orders and {} cycles of 2000 random graphs", cycles);
+
+    // Kahn's variants agree with DFS on whether there is an order.
+    for round in 0..2000 {
+        let vertices = 1 + round % 40;
+        let graph = random_graph(vertices, rng.gen_range(0..3 * vertices), round % 2 == 0, rng);
+        match graph.topological_order() {
+            Ok(_) => {
+                assert!(is_topological(&graph, &graph.kahn().unwrap()));
+                let smallest = graph.smallest_order().unwrap();
+                assert!(is_topological(&graph, &smallest));
+                if vertices <= 12 {
+                    assert_eq!(graph.all_orders().next(), Some(smallest));
+                }
+                assert!(
+                    is_layering(&graph, &graph.layers().unwrap()),
+                    "Wrong layers of {:?}",
+                    graph
+                );
+            }
+            Err(_) => {
+                assert!(is_cycle(&graph, &graph.kahn().unwrap_err()));
+                assert!(is_cycle(&graph, &graph.smallest_order().unwrap_err()));
+                assert!(is_cycle(&graph, &graph.layers().unwrap_err()));
+                assert_eq!(graph.all_orders().next(), None);
+            }
+        }
+    }
+    println!("Checked Kahn's order, smallest order and layers of 2000 random graphs");
+
+    // All orders of small DAGs, increasing and as many as counted.
+    let mut total = 0;
+    for round in 0..500 {
+        let vertices = round % 9;
+        let graph = random_graph(vertices, rng.gen_range(0..2 * vertices + 1), true, rng);
+        let orders = graph.all_orders().collect::<Vec<Vec<usize>>>();
+        assert!(orders.iter().all(|order| is_topological(&graph, order)));
+        assert!(orders.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(orders.len() as u64, count_orders(&graph));
+        total += orders.len();
+    }
+    println!("Checked all {} orders of 500 small DAGs", total);
+
+    // A chain far deeper than the call stack allows, and the same chain closed into a cycle.
+    let n = 1_000_000;
+    let mut graph = Graph::new(n);
+    for vertex in 1..n {
+        graph.add_edge(vertex - 1, vertex);
+    }
+    let start = Instant::now();
+    assert!(is_topological(&graph, &graph.topological_order().unwrap()));
+    println!("Chain of {} vertices sorted in {:.2?}", n, start.elapsed());
+
+    graph.add_edge(n - 1, 0);
+    assert_eq!(graph.topological_order().unwrap_err().vertices.len(), n);
+}
+
 fn main() {
+    // Ensure proper usage.
+    let mut args = env::args().skip(1).collect::<Vec<String>>();
+    let mut rng = match Rng::from_args(&mut args) {
+        Some(rng) if args.is_empty() => rng,
+        _ => {
+            eprintln!("usage: ./topological_sort [--seed <seed>]");
+            process::exit(1);
+        }
+    };
+    println!("Seed = {}", rng.seed());
+    check(&mut rng);
+
     let mut graph = Graph::new(5);
     graph.add_edge(3, 1);
     graph.add_edge(3, 4);
@@ -85,6 +480,41 @@ fn main() {
     graph2.add_edge(2, 3);
     graph2.add_edge(3, 1);
 
-    println!("{:?}", graph.topological_order());
+    println!("\n{:?}", graph.topological_order());
     println!("{:?}", graph2.topological_order());
+    println!("Kahn: {:?}", graph2.kahn());
+    println!("Smallest: {:?}", graph2.smallest_order());
+    println!("Layers: {:?}", graph2.layers());
+    println!("All orders: {:?}", graph2.all_orders().collect::<Vec<Vec<usize>>>());
+
+    // Build dependencies, every target pointing at the ones that need it built first, and steps running together.
+    let targets = ["app", "lib", "codegen", "util", "docs"];
+    let mut build = Graph::new(targets.len());
+    for &(v1, v2) in [(3, 1), (2, 1), (1, 0), (3, 0), (3, 4)].iter() {
+        build.add_edge(v1, v2);
+    }
+    for (step, layer) in build.layers().unwrap().iter().enumerate() {
+        println!(
+            "Build step {}: {:?}",
+            step + 1,
+            layer.iter().map(|&v| targets[v]).collect::<Vec<&str>>()
+        );
+    }
+
+    // Build dependencies, where app needs lib, lib needs codegen, and codegen needs app back.
+    let targets = ["app", "lib", "codegen", "util"];
+    let mut build = Graph::new(targets.len());
+    for &(v1, v2) in [(1, 0), (3, 1), (2, 1), (0, 2), (3, 2)].iter() {
+        build.add_edge(v1, v2);
+    }
+    match build.topological_order() {
+        Ok(order) => println!(
+            "Build order: {:?}",
+            order.iter().map(|&v| targets[v]).collect::<Vec<&str>>()
+        ),
+        Err(cycle) => {
+            let names = cycle.vertices.iter().chain(cycle.vertices.first()).map(|&v| targets[v]);
+            println!("Dependency cycle: {}", names.collect::<Vec<&str>>().join(" -> "));
+        }
+    }
 }
@@ -1,145 +1,162 @@
 /**
  * Create our own 0-index based Binary Min-Heap (also called priority queue).
  * Supported operations: Insert, Delete, Extract top value, all in O(log n) time.
+ *
+ * Bubble up and bubble down work on plain slices with any ordering, so they are also used to heap sort in place.
  */
 
 // Heap implemented using vector.
 #[derive(Debug)]
-struct Heap {
-    array: Vec<i32>,
+pub struct Heap<T> {
+    array: Vec<T>,
 }
 
-impl Heap {
+impl<T: Ord> Default for Heap<T> {
+    fn default() -> Heap<T> {
+        Heap::new()
+    }
+}
+
+impl<T: Ord> Heap<T> {
     // Initialize an empty heap.
-    fn new() -> Heap {
+    pub fn new() -> Heap<T> {
         Heap { array: Vec::new() }
     }
 
     // Inserts an element in a heap.
-    fn insert(&mut self, value: i32) {
+    pub fn insert(&mut self, value: T) {
         // Push element to end of array.
         self.array.push(value);
 
         // Bubble up this value to satisfy heap constraint.
-        self.bubble_up(self.array.len() - 1);
+        let last = self.array.len() - 1;
+        bubble_up_by(&mut self.array, last, &mut |a: &T, b: &T| a < b);
     }
 
     // Extracts the top element from the heap.
-    fn extract(&mut self) -> Option<i32> {
+    pub fn extract(&mut self) -> Option<T> {
         if self.array.is_empty() {
             return None;
         }
 
-        let top_value = *self.array.first().unwrap();
-
         // Bring the last element in place of first.
-        let last = self.array.len() - 1;
-        self.array.swap(last, 0);
-        self.array.pop();
-
-        // Heap is empty now.
-        if self.array.is_empty() {
-            return Some(top_value);
-        }
+        let top_value = self.array.swap_remove(0);
 
         // Bubble down the top element to correct position.
-        self.bubble_down(0);
+        bubble_down_by(&mut self.array, 0, &mut |a: &T, b: &T| a < b);
 
         Some(top_value)
     }
 
     // Deletes and returns this value from the heap.
-    fn delete(&mut self, value: i32) -> Option<i32> {
-        // Find the index of the element to delete.
-        let mut index: Option<usize> = None;
-        for (i, e) in self.array.iter().enumerate() {
-            if *e == value {
-                index = Some(i);
-                break;
-            }
-        }
-
-        // Element not found.
-        if index == None {
-            return None;
-        }
+    pub fn delete(&mut self, value: T) -> Option<T> {
+        // Find the index of the element to delete, if it exists.
+        let index = self.array.iter().position(|e| *e == value)?;
 
         // Swap this with last element.
-        let last = self.array.len() - 1;
-        self.array.swap(index.unwrap(), last);
-        let deleted_element = self.array.pop();
+        let deleted_element = self.array.swap_remove(index);
 
-        // Heap is empty now.
-        if self.array.is_empty() {
-            return deleted_element;
+        // Element that took its place can be smaller or larger, move it whichever way is needed.
+        if index < self.array.len() {
+            bubble_up_by(&mut self.array, index, &mut |a: &T, b: &T| a < b);
+            bubble_down_by(&mut self.array, index, &mut |a: &T, b: &T| a < b);
         }
 
-        // Bubble down the top element to correct position.
-        self.bubble_down(index.unwrap());
+        Some(deleted_element)
+    }
+}
 
-        deleted_element
+// Bubble up the element at [index] while it comes before its parent according to [less].
+// Returns the number of swaps done.
+pub fn bubble_up_by<T, F: FnMut(&T, &T) -> bool>(array: &mut [T], index: usize, less: &mut F) -> usize {
+    let mut child_index = index;
+    let mut parent_index = get_parent_index(child_index);
+    let mut swaps = 0;
+
+    // Keep bubbling up.
+    while parent_index.is_some() && less(&array[child_index], &array[parent_index.unwrap()]) {
+        array.swap(parent_index.unwrap(), child_index);
+        swaps += 1;
+        child_index = parent_index.unwrap();
+        parent_index = get_parent_index(child_index);
     }
 
-    // Bubble up the element at [index] until it satisfies heap property.
-    fn bubble_up(&mut self, index: usize) {
-        let mut child_index = index;
-        let mut parent_index = self.get_parent_index(child_index);
+    swaps
+}
 
-        // Keep bubbling up.
-        while parent_index != None && self.array[parent_index.unwrap()] > self.array[child_index] {
-            self.array.swap(parent_index.unwrap(), child_index);
-            child_index = parent_index.unwrap();
-            parent_index = self.get_parent_index(child_index);
-        }
+// Bubble down the element at [index] while one of its children comes before it according to [less].
+// Returns the number of swaps done.
+pub fn bubble_down_by<T, F: FnMut(&T, &T) -> bool>(array: &mut [T], index: usize, less: &mut F) -> usize {
+    let mut parent_index = index;
+    let mut child_index = get_smallest_child_index(array, parent_index, less);
+    let mut swaps = 0;
+
+    while child_index.is_some() && less(&array[child_index.unwrap()], &array[parent_index]) {
+        array.swap(parent_index, child_index.unwrap());
+        swaps += 1;
+        parent_index = child_index.unwrap();
+        child_index = get_smallest_child_index(array, parent_index, less);
     }
 
-    // Bubble down the element at [index] until it satisfies heap property.
-    fn bubble_down(&mut self, index: usize) {
-        let mut parent_index = index;
-        let mut child_index = self.get_smallest_child_index(parent_index);
+    swaps
+}
 
-        while child_index != None && self.array[parent_index] > self.array[child_index.unwrap()] {
-            self.array.swap(parent_index, child_index.unwrap());
-            parent_index = child_index.unwrap();
-            child_index = self.get_smallest_child_index(parent_index);
-        }
-    }
+// Sorts the array in place in O(n log n) time, ascending according to [less]. Returns the number of swaps done.
+pub fn heap_sort_by<T, F: FnMut(&T, &T) -> bool>(array: &mut [T], mut less: F) -> usize {
+    // Largest element stays on top, so it can be moved to the end of the array.
+    let mut greater = |a: &T, b: &T| less(b, a);
+    let mut swaps = 0;
 
-    // Given the child index, returns the parent index or None.
-    fn get_parent_index(&self, child_index: usize) -> Option<usize> {
-        match child_index {
-            0 => None,
-            index => Some((index - 1) / 2),
-        }
+    // Build the heap bottom up, leaves are already heaps.
+    for index in (0..array.len() / 2).rev() {
+        swaps += bubble_down_by(array, index, &mut greater);
     }
 
-    // Given the parent index, returns the index of smallest child or none.
-    fn get_smallest_child_index(&self, parent_index: usize) -> Option<usize> {
-        let first_child = match 2 * parent_index + 1 < self.array.len() {
-            true => Some(2 * parent_index + 1),
-            false => None,
-        };
+    // Move the top to the end, and restore the heap on the remaining elements.
+    for end in (1..array.len()).rev() {
+        array.swap(0, end);
+        swaps += 1 + bubble_down_by(&mut array[..end], 0, &mut greater);
+    }
 
-        // Socond child won't exist if first does not.
-        if first_child == None {
-            return None;
-        }
+    swaps
+}
 
-        let second_child = match 2 * parent_index + 2 < self.array.len() {
-            true => Some(2 * parent_index + 2),
-            false => None,
-        };
+// Given the child index, returns the parent index or None.
+fn get_parent_index(child_index: usize) -> Option<usize> {
+    match child_index {
+        0 => None,
+        index => Some((index - 1) / 2),
+    }
+}
 
-        if second_child == None {
-            return first_child;
-        }
+// Given the parent index, returns the index of smallest child or none.
+fn get_smallest_child_index<T, F: FnMut(&T, &T) -> bool>(
+    array: &[T],
+    parent_index: usize,
+    less: &mut F,
+) -> Option<usize> {
+    let first_child = match 2 * parent_index + 1 < array.len() {
+        true => Some(2 * parent_index + 1),
+        false => None,
+    };
+
+    // Socond child won't exist if first does not.
+    first_child?;
+
+    let second_child = match 2 * parent_index + 2 < array.len() {
+        true => Some(2 * parent_index + 2),
+        false => None,
+    };
+
+    if second_child.is_none() {
+        return first_child;
+    }
 
-        // Return the index of smaller of two children.
-        if self.array[first_child.unwrap()] < self.array[second_child.unwrap()] {
-            first_child
-        } else {
-            second_child
-        }
+    // Return the index of smaller of two children.
+    if less(&array[first_child.unwrap()], &array[second_child.unwrap()]) {
+        first_child
+    } else {
+        second_child
     }
 }
 
@@ -157,4 +174,22 @@ fn main() {
 
     min_heap.delete(3);
     println!("{:?}", min_heap);
+
+    // Deleting 12 moves the last element, 8, under 10, so it has to bubble up.
+    let mut min_heap = Heap::new();
+    for item in [1, 10, 2, 11, 12, 3, 4, 13, 14, 15, 16, 5, 6, 7, 8] {
+        min_heap.insert(item);
+    }
+    assert_eq!(min_heap.delete(12), Some(12));
+    assert!((1..min_heap.array.len()).all(|index| min_heap.array[(index - 1) / 2] <= min_heap.array[index]));
+    let extracted = (0..14).filter_map(|_| min_heap.extract()).collect::<Vec<i32>>();
+    assert!(
+        extracted.windows(2).all(|pair| pair[0] <= pair[1]),
+        "Extracted out of order: {:?}",
+        extracted
+    );
+
+    let mut array = [5, 2, 9, 1, 5, 6, 0];
+    heap_sort_by(&mut array, |a, b| a < b);
+    println!("{:?}", array);
 }
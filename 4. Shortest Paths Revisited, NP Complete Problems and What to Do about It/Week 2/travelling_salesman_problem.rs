@@ -6,10 +6,10 @@ use std::error::Error;
 use std::fs;
 
 // Point representation in 2D space.
-struct Point {
-    x: f64,
-    y: f64,
-}
+#[allow(dead_code)]
+#[path = "../../1. Divide and Conquer, Sorting and Searching, and Randomized Algorithms/Week 2/point.rs"]
+mod point;
+use point::Point;
 
 /// Build distance matrix from city coordinates.
 fn build_distance_matrix(coordinates: &[Point]) -> Vec<Vec<f64>> {
@@ -25,7 +25,7 @@ fn build_distance_matrix(coordinates: &[Point]) -> Vec<Vec<f64>> {
                 // Calculate using euclidean distance.
                 let one = &coordinates[i];
                 let two = &coordinates[j];
-                distance_matrix[i][j] = f64::sqrt((one.x - two.x).powi(2) + (one.y - two.y).powi(2));
+                distance_matrix[i][j] = one.euclidean_distance(two);
             }
         }
     }
@@ -5,21 +5,13 @@
 use std::{error::Error, fs};
 
 // Coordinates for the city.
-#[derive(Debug)]
-struct Coordinate {
-    x: f64,
-    y: f64,
-}
-
-impl Coordinate {
-    // Return squared euclidean distance.
-    fn squared_euclidean_distance(&self, other: &Coordinate) -> f64 {
-        (self.x - other.x).powi(2) + (self.y - other.y).powi(2)
-    }
-}
+#[allow(dead_code)]
+#[path = "../../1. Divide and Conquer, Sorting and Searching, and Randomized Algorithms/Week 2/point.rs"]
+mod point;
+use point::Point;
 
 // Calculate shortest distance using nearest neighbour, rounded down to the nearest integer.
-fn travelling_salesman_heuristic(cities: &[Coordinate]) -> u32 {
+fn travelling_salesman_heuristic(cities: &[Point]) -> u32 {
     // Mark all cities as unvisited.
     let mut visited = vec![false; cities.len()];
 
@@ -79,7 +71,7 @@ fn main() -> Result<(), Box<dyn Error>> {
 
     // Total number of cities.
     let num_cities = file_iter.next().unwrap().parse::<usize>()?;
-    let mut cities = Vec::<Coordinate>::with_capacity(num_cities);
+    let mut cities = Vec::<Point>::with_capacity(num_cities);
 
     for line in file_iter {
         let mut row = line.split_whitespace();
@@ -89,7 +81,7 @@ fn main() -> Result<(), Box<dyn Error>> {
         // Get the coordinates.
         let x = row.next().unwrap().parse::<f64>()?;
         let y = row.next().unwrap().parse::<f64>()?;
-        cities.push(Coordinate { x, y });
+        cities.push(Point::new(x, y));
     }
 
     println!("{}", travelling_salesman_heuristic(&cities));